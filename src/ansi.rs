@@ -0,0 +1,337 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// A line of container output with its escape sequences taken out, and the
+/// styles its SGR codes set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StyledText {
+    /// What is left to display, without escape sequences or control characters
+    pub text: String,
+    /// Byte ranges of `text` in a non-default style, in order
+    pub styles: Vec<(Range<usize>, Style)>,
+}
+
+impl StyledText {
+    /// Parses a line of output. SGR sequences become styles, every other
+    /// escape sequence is dropped, as are control characters apart from tabs
+    /// and newlines, which become spaces.
+    pub fn parse(input: &str) -> Self {
+        let mut parsed = Self::default();
+        let mut style = Style::default();
+        let mut run_start = 0;
+        let mut parser = Parser::default();
+        for c in input.chars() {
+            match parser.feed(c) {
+                Some(Token::Text(c)) => parsed.text.push(c),
+                Some(Token::Control('\t')) => parsed.text.push_str("    "),
+                Some(Token::Control('\n')) => parsed.text.push(' '),
+                Some(Token::Control(_)) | None => {}
+                Some(Token::Sgr(params)) => {
+                    let next = apply_sgr(style, &params);
+                    if next != style {
+                        parsed.push_run(run_start, style);
+                        run_start = parsed.text.len();
+                        style = next;
+                    }
+                }
+            }
+        }
+        parsed.push_run(run_start, style);
+        parsed
+    }
+
+    /// Ends the run of `style` started at `start`.
+    fn push_run(&mut self, start: usize, style: Style) {
+        if start < self.text.len() && style != Style::default() {
+            self.styles.push((start..self.text.len(), style));
+        }
+    }
+}
+
+/// The text of a line of output as the TUI shows it, without any escape
+/// sequence.
+pub fn strip(input: &str) -> String {
+    StyledText::parse(input).text
+}
+
+/// A line of output made safe to print on a terminal: escape sequences and
+/// control characters other than tabs are dropped.
+pub fn sanitize(input: &str) -> Cow<'_, str> {
+    if !input.chars().any(|c| c.is_control() && c != '\t') {
+        return Cow::Borrowed(input);
+    }
+    let mut parser = Parser::default();
+    Cow::Owned(
+        input
+            .chars()
+            .filter_map(|c| match parser.feed(c)? {
+                Token::Text(c) | Token::Control(c @ '\t') => Some(c),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+/// What a character of terminal output completes, as told by [`Parser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// A printable character
+    Text(char),
+    /// A C0 or C1 control character outside of any sequence
+    Control(char),
+    /// The parameters of a `CSI ... m` sequence
+    Sgr(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    /// `ESC` followed by intermediate bytes, as in `ESC ( B`
+    EscapeIntermediate,
+    Csi,
+    /// A CSI sequence that cannot be an SGR one, skipped to its final byte
+    CsiIgnore,
+    /// OSC, terminated by BEL or ST
+    Osc,
+    /// DCS, SOS, PM and APC strings, terminated by ST
+    String,
+}
+
+/// Longest CSI parameter string kept, longer ones cannot be sane SGR sequences.
+const MAX_CSI_PARAMS: usize = 64;
+
+/// A state machine recognising the escape sequences in terminal output, after
+/// the DEC VT500 parser: CSI, OSC, DCS, SOS, PM and APC sequences and bare
+/// escapes, introduced by `ESC` or by their 8-bit C1 control.
+///
+/// Only printable characters come out as [`Token::Text`], whatever the input.
+#[derive(Debug, Default)]
+pub struct Parser {
+    state: State,
+    params: String,
+}
+
+impl Parser {
+    /// Feeds one character, returning what it completes, if anything.
+    pub fn feed(&mut self, c: char) -> Option<Token> {
+        match c {
+            // Cancel any sequence
+            '\x18' | '\x1a' => {
+                self.state = State::Ground;
+                return None;
+            }
+            '\x1b' => {
+                self.state = State::Escape;
+                return None;
+            }
+            // C1 controls, the 8-bit forms of ESC Fe sequences
+            '\u{90}' | '\u{98}' | '\u{9e}' | '\u{9f}' => {
+                self.state = State::String;
+                return None;
+            }
+            '\u{9b}' => {
+                self.start_csi();
+                return None;
+            }
+            '\u{9d}' => {
+                self.state = State::Osc;
+                return None;
+            }
+            // ST, or a C1 control ending whatever came before
+            '\u{80}'..='\u{9f}' => {
+                let state = std::mem::take(&mut self.state);
+                return (state == State::Ground).then_some(Token::Control(c));
+            }
+            _ => {}
+        }
+
+        match self.state {
+            // DEL is padding, ignored everywhere
+            _ if c == '\x7f' => None,
+            State::Ground if c.is_control() => Some(Token::Control(c)),
+            State::Ground => Some(Token::Text(c)),
+            // Controls inside a sequence still take effect
+            State::Escape | State::EscapeIntermediate | State::Csi | State::CsiIgnore
+                if c < '\x20' =>
+            {
+                Some(Token::Control(c))
+            }
+            State::Escape => match c {
+                '[' => {
+                    self.start_csi();
+                    None
+                }
+                ']' => {
+                    self.state = State::Osc;
+                    None
+                }
+                'P' | 'X' | '^' | '_' => {
+                    self.state = State::String;
+                    None
+                }
+                '\x20'..='\x2f' => {
+                    self.state = State::EscapeIntermediate;
+                    None
+                }
+                _ => self.end_escape(c),
+            },
+            State::EscapeIntermediate => match c {
+                '\x20'..='\x2f' => None,
+                _ => self.end_escape(c),
+            },
+            State::Csi | State::CsiIgnore => match c {
+                '\x40'..='\x7e' => {
+                    let sgr = self.state == State::Csi && c == 'm';
+                    self.state = State::Ground;
+                    sgr.then(|| Token::Sgr(std::mem::take(&mut self.params)))
+                }
+                '0'..='9' | ';' | ':' if self.params.len() < MAX_CSI_PARAMS => {
+                    self.params.push(c);
+                    None
+                }
+                // Private markers, intermediates and anything else
+                _ => {
+                    self.state = State::CsiIgnore;
+                    None
+                }
+            },
+            State::Osc => {
+                if c == '\x07' {
+                    self.state = State::Ground;
+                }
+                None
+            }
+            State::String => None,
+        }
+    }
+
+    fn start_csi(&mut self) {
+        self.state = State::Csi;
+        self.params.clear();
+    }
+
+    /// The final character of an `ESC` sequence, or what breaks it off.
+    fn end_escape(&mut self, c: char) -> Option<Token> {
+        self.state = State::Ground;
+        // Printable text that was never part of a sequence
+        (!c.is_ascii()).then_some(Token::Text(c))
+    }
+}
+
+/// Applies the parameters of an SGR sequence, `ESC [ params m`.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let mut params = params.split(';');
+    while let Some(param) = params.next() {
+        // ITU T.416 form, the colour is inside the parameter: 38:5:n or 38:2:[id]:r:g:b
+        if let Some((code, rest)) = param.split_once(':') {
+            let mut sub: Vec<&str> = rest.split(':').collect();
+            if sub.len() == 5 && sub[0] == "2" {
+                // Drop the colour space id
+                sub.remove(1);
+            }
+            let color = extended_color(&mut sub.into_iter());
+            style = match code {
+                "38" => set_fg(style, color),
+                "48" => set_bg(style, color),
+                "58" => style,
+                _ => apply_sgr(style, code),
+            };
+            continue;
+        }
+
+        let code: u8 = match param {
+            "" => 0,
+            param => match param.parse() {
+                Ok(code) => code,
+                Err(_) => continue,
+            },
+        };
+        // Blinking and hidden text are left out, they only get in the way of reading logs
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 | 21 => style.add_modifier(Modifier::UNDERLINED),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => without(style, Modifier::BOLD | Modifier::DIM),
+            23 => without(style, Modifier::ITALIC),
+            24 => without(style, Modifier::UNDERLINED),
+            27 => without(style, Modifier::REVERSED),
+            29 => without(style, Modifier::CROSSED_OUT),
+            30..=37 => style.fg(basic_color(code - 30)),
+            38 => set_fg(style, extended_color(&mut params)),
+            39 => set_fg(style, None),
+            40..=47 => style.bg(basic_color(code - 40)),
+            48 => set_bg(style, extended_color(&mut params)),
+            49 => set_bg(style, None),
+            // Underline colour, only its parameters need skipping
+            58 => {
+                extended_color(&mut params);
+                style
+            }
+            90..=97 => style.fg(bright_color(code - 90)),
+            100..=107 => style.bg(bright_color(code - 100)),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// The colour of a 38 or 48 code: `5;n` from the 256 colour palette or
+/// `2;r;g;b`.
+fn extended_color<'a>(params: &mut impl Iterator<Item = &'a str>) -> Option<Color> {
+    let mut next = || params.next().and_then(|param| param.parse::<u8>().ok());
+    match next()? {
+        5 => Some(Color::Indexed(next()?)),
+        2 => Some(Color::Rgb(next()?, next()?, next()?)),
+        _ => None,
+    }
+}
+
+fn set_fg(mut style: Style, color: Option<Color>) -> Style {
+    style.fg = color;
+    style
+}
+
+fn set_bg(mut style: Style, color: Option<Color>) -> Style {
+    style.bg = color;
+    style
+}
+
+/// Unlike [`Style::remove_modifier`], leaves no trace of the modifier so that
+/// a style set and unset again is the default one.
+fn without(mut style: Style, modifier: Modifier) -> Style {
+    style.add_modifier.remove(modifier);
+    style
+}
+
+fn basic_color(index: u8) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::Gray,
+    }
+}
+
+fn bright_color(index: u8) -> Color {
+    match index {
+        0 => Color::DarkGray,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
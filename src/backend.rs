@@ -0,0 +1,102 @@
+use docker_api::conn::TtyChunk;
+use docker_api::models::{ContainerInspect200Response, ContainerSummary, EventMessage};
+use docker_api::opts::{ContainerListOpts, EventsOpts, LogsOpts};
+use docker_api::Docker;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
+use tokio::sync::mpsc;
+
+/// The Docker API calls the log sources rely on.
+///
+/// [`Docker`] implements it against a real daemon; `fake::FakeBackend`, built
+/// with the `testing` feature, keeps everything in memory so the sources can
+/// be driven without one.
+pub trait Backend: Send + Sync {
+    fn list<'a>(
+        &'a self,
+        opts: &'a ContainerListOpts,
+    ) -> BoxFuture<'a, docker_api::Result<Vec<ContainerSummary>>>;
+
+    fn inspect<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, docker_api::Result<ContainerInspect200Response>>;
+
+    /// The multiplexed log stream of a container.
+    fn logs<'a>(
+        &'a self,
+        id: &'a str,
+        opts: &LogsOpts,
+    ) -> BoxStream<'a, docker_api::Result<TtyChunk>>;
+
+    fn events<'a>(&'a self, opts: &EventsOpts) -> BoxStream<'a, docker_api::Result<EventMessage>>;
+
+    /// Checks that the daemon answers.
+    fn ping(&self) -> BoxFuture<'_, docker_api::Result<()>>;
+}
+
+impl Backend for Docker {
+    fn list<'a>(
+        &'a self,
+        opts: &'a ContainerListOpts,
+    ) -> BoxFuture<'a, docker_api::Result<Vec<ContainerSummary>>> {
+        async move { self.containers().list(opts).await }.boxed()
+    }
+
+    fn inspect<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, docker_api::Result<ContainerInspect200Response>> {
+        async move { self.containers().get(id).inspect().await }.boxed()
+    }
+
+    fn logs<'a>(
+        &'a self,
+        id: &'a str,
+        opts: &LogsOpts,
+    ) -> BoxStream<'a, docker_api::Result<TtyChunk>> {
+        // The stream borrows the container handle, which cannot outlive this
+        // call, so it is read by a task that hands the chunks over
+        let container = self.containers().get(id);
+        let opts = opts.clone();
+        let (sender, receiver) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let mut logs = container.logs(&opts);
+            loop {
+                tokio::select! {
+                    chunk = logs.next() => match chunk {
+                        Some(chunk) => {
+                            if sender.send(chunk).await.is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    },
+                    // Stop reading as soon as nobody listens any more
+                    _ = sender.closed() => break,
+                }
+            }
+        });
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (chunk, receiver))
+        })
+        .boxed()
+    }
+
+    fn events<'a>(&'a self, opts: &EventsOpts) -> BoxStream<'a, docker_api::Result<EventMessage>> {
+        Docker::events(self, opts).boxed()
+    }
+
+    /// docker-api insists on every header Docker sends, which Podman and other
+    /// compatible daemons may leave out, so any HTTP success counts as alive.
+    fn ping(&self) -> BoxFuture<'_, docker_api::Result<()>> {
+        async move {
+            match Docker::ping(self).await {
+                Ok(_) | Err(docker_api::Error::InvalidResponse(_)) => Ok(()),
+                Err(err) => Err(err),
+            }
+        }
+        .boxed()
+    }
+}
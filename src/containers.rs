@@ -0,0 +1,113 @@
+use crate::backend::Backend;
+use crate::error::Error;
+use docker_api::models::{ContainerSummary, EventActor};
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+/// Identity of a container, attached to every JSON event.
+#[derive(Serialize, Debug, Clone)]
+pub struct ContainerMeta {
+    /// Alias of the daemon running the container, when tailing several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub id: String,
+    pub name: String,
+    pub image: Option<String>,
+    #[serde(skip)]
+    pub labels: HashMap<String, String>,
+}
+
+impl ContainerMeta {
+    /// Builds the metadata from a `containers().list()` entry.
+    pub fn from_summary(summary: &ContainerSummary) -> Option<Self> {
+        Some(Self {
+            host: None,
+            id: summary.id.clone()?,
+            name: strip_name(summary.names.as_ref()?.first()?),
+            image: summary.image.clone(),
+            labels: summary.labels.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Builds the metadata from a container event actor, whose attributes
+    /// hold the name, the image and every label of the container.
+    pub fn from_event_actor(actor: &EventActor) -> Option<Self> {
+        let mut attributes = actor.attributes.clone()?;
+        Some(Self {
+            host: None,
+            id: actor.id.clone()?,
+            name: strip_name(&attributes.remove("name")?),
+            image: attributes.remove("image"),
+            labels: attributes,
+        })
+    }
+
+    /// The name shown in output, prefixed with the host alias if any.
+    pub fn display_name(&self) -> String {
+        match &self.host {
+            Some(host) => format!("{}/{}", host, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+fn strip_name(name: &str) -> String {
+    name.strip_prefix('/').unwrap_or(name).to_owned()
+}
+
+/// Caches container metadata so each container is inspected at most once.
+///
+/// The cache is filled from list and event data, which already carry the
+/// name, image and labels, so most containers are never inspected at all.
+/// There is one cache per daemon, which stamps its alias on every entry.
+#[derive(Debug, Default)]
+pub struct ContainerCache {
+    host: Option<String>,
+    entries: Mutex<HashMap<String, ContainerMeta>>,
+}
+
+impl ContainerCache {
+    pub fn new(host: Option<String>) -> Self {
+        Self {
+            host,
+            entries: Mutex::default(),
+        }
+    }
+
+    pub async fn insert(&self, mut meta: ContainerMeta) {
+        meta.host = self.host.clone();
+        self.entries.lock().await.insert(meta.id.clone(), meta);
+    }
+
+    /// Forgets a container that is no longer watched. It is looked up again
+    /// from list or event data if it starts again.
+    pub async fn remove(&self, id: &str) {
+        self.entries.lock().await.remove(id);
+    }
+
+    /// Returns the cached metadata, inspecting the container on a miss.
+    pub async fn get(&self, backend: &dyn Backend, id: &str) -> Result<ContainerMeta, Error> {
+        if let Some(meta) = self.entries.lock().await.get(id) {
+            return Ok(meta.clone());
+        }
+
+        let info = backend.inspect(id).await.map_err(Error::Docker)?;
+        let name = info.name.as_deref().ok_or_else(|| {
+            Error::Docker(docker_api::Error::InvalidResponse(format!(
+                "container {} has no name",
+                id
+            )))
+        })?;
+        let config = info.config.as_ref();
+        let meta = ContainerMeta {
+            host: self.host.clone(),
+            id: id.to_string(),
+            name: strip_name(name),
+            image: config.and_then(|c| c.image.clone()),
+            labels: config.and_then(|c| c.labels.clone()).unwrap_or_default(),
+        };
+        self.insert(meta.clone()).await;
+        Ok(meta)
+    }
+}
@@ -0,0 +1,492 @@
+use crate::backend::Backend;
+use crate::error::{self, Error, Result};
+use docker_api::Docker;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+const DEFAULT_URL: &str = "unix:///var/run/docker.sock";
+
+/// How long a candidate socket gets to answer `/_ping` during discovery.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Options choosing the Docker daemon to talk to.
+#[derive(clap::Args, Debug, Clone)]
+pub struct ConnectionArgs {
+    /// Docker daemon URL as URL or ALIAS=URL, repeatable to tail several daemons [default: $DOCKER_HOST, the current context or the first local socket that answers]
+    #[clap(short, long, value_parser, global = true)]
+    pub url: Vec<String>,
+    /// File listing daemons to tail, one URL or ALIAS=URL per line
+    #[clap(long, value_parser, global = true)]
+    pub hosts_file: Option<PathBuf>,
+    /// Name of a docker CLI context to connect to [default: $DOCKER_CONTEXT or the current context]
+    #[clap(long, value_parser, global = true, conflicts_with_all = &["url", "hosts-file"])]
+    pub context: Option<String>,
+    /// Use TLS for tcp:// URLs, implied by $DOCKER_TLS_VERIFY and the --tls-* files. The daemon is always verified
+    #[clap(long, value_parser, global = true)]
+    pub tls: bool,
+    /// TLS client certificate for tcp:// URLs
+    #[clap(long, value_parser, global = true)]
+    pub tls_cert: Option<PathBuf>,
+    /// TLS client key for tcp:// URLs
+    #[clap(long, value_parser, global = true)]
+    pub tls_key: Option<PathBuf>,
+    /// CA certificate used to verify the daemon for tcp:// URLs [default: ca.pem of $DOCKER_CERT_PATH or the context, else the system CAs]
+    #[clap(long, value_parser, global = true)]
+    pub tls_ca: Option<PathBuf>,
+}
+
+/// Client certificate material for a TLS connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// CA the daemon certificate is checked against, otherwise it must be
+    /// trusted by the system
+    pub ca: Option<PathBuf>,
+}
+
+/// A resolved daemon address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// Name shown next to container names when tailing several daemons
+    pub alias: Option<String>,
+    pub url: String,
+    pub tls: Option<TlsFiles>,
+    /// Nothing was configured, `url` is only the fallback for `discover`
+    pub probe: bool,
+}
+
+impl Endpoint {
+    /// Resolves the daemons given with `--url` and `--hosts-file`, or the
+    /// single default one. Every endpoint gets an alias when there are
+    /// several of them.
+    pub fn resolve_all(args: &ConnectionArgs) -> Result<Vec<Self>> {
+        let mut specs = args.url.clone();
+        if let Some(path) = &args.hosts_file {
+            let content = fs::read_to_string(path).map_err(|err| {
+                Error::Config(format!(
+                    "cannot read hosts file {}: {}",
+                    path.display(),
+                    err
+                ))
+            })?;
+            specs.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        if specs.is_empty() {
+            return Ok(vec![Self::resolve(args)?]);
+        }
+
+        let config_dir = config_dir();
+        let mut endpoints = Vec::new();
+        for spec in &specs {
+            let (alias, url) = parse_host_spec(spec)?;
+            let mut endpoint = Self::from_url(url.to_string(), args, &config_dir)?;
+            endpoint.alias = alias.map(str::to_string);
+            endpoints.push(endpoint);
+        }
+        if endpoints.len() > 1 {
+            assign_aliases(&mut endpoints);
+        }
+        Ok(endpoints)
+    }
+
+    /// Resolves the daemon the way the docker CLI does when no URL is given:
+    /// `--context`, then `DOCKER_HOST`, then `DOCKER_CONTEXT`, then
+    /// the current context of `config.json`, then the default socket.
+    fn resolve(args: &ConnectionArgs) -> Result<Self> {
+        let config_dir = config_dir();
+
+        if let Some(name) = &args.context {
+            return Self::from_context(name, args, &config_dir);
+        }
+        if let Some(host) = env_var("DOCKER_HOST") {
+            return Self::from_url(host, args, &config_dir);
+        }
+        let context = env_var("DOCKER_CONTEXT").or_else(|| current_context(&config_dir));
+        match context {
+            // "default" is the docker CLI's name for the implicit local daemon
+            Some(name) if name != "default" => Self::from_context(&name, args, &config_dir),
+            _ => Ok(Self {
+                alias: None,
+                url: DEFAULT_URL.to_string(),
+                tls: None,
+                probe: true,
+            }),
+        }
+    }
+
+    /// Replaces the fallback URL with the first known local socket whose
+    /// daemon answers `/_ping`, when no endpoint was configured.
+    pub async fn discover(self) -> Self {
+        let runtime_dir = env_var("XDG_RUNTIME_DIR").map(PathBuf::from).or_else(|| {
+            // Where XDG_RUNTIME_DIR usually points, for shells that do not set it
+            fs::metadata("/proc/self")
+                .ok()
+                .map(|m| PathBuf::from(format!("/run/user/{}", owner_uid(&m))))
+        });
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let candidates = socket_candidates(runtime_dir.as_deref(), home.as_deref());
+        self.discover_among(&candidates).await
+    }
+
+    /// Like [`discover`](Self::discover), trying the sockets of `candidates`
+    /// in order.
+    pub async fn discover_among(mut self, candidates: &[PathBuf]) -> Self {
+        if !self.probe {
+            return self;
+        }
+        for path in candidates {
+            if !path.exists() {
+                continue;
+            }
+            let url = format!("unix://{}", path.display());
+            if ping(&url).await {
+                error::note(&format!("using the daemon at {}", url));
+                self.url = url;
+                return self;
+            }
+            error::note(&format!("no daemon answered at {}", url));
+        }
+        self
+    }
+
+    /// An explicit URL, secured by `--tls`, the `--tls-*` files or
+    /// `DOCKER_TLS_VERIFY`. Like for the docker CLI, `DOCKER_CERT_PATH`
+    /// only says where the certificates are. Like for a context, the daemon
+    /// is checked against `ca.pem` when there is one, otherwise against the
+    /// system CAs, as docker-api cannot skip the check.
+    fn from_url(url: String, args: &ConnectionArgs, config_dir: &Path) -> Result<Self> {
+        if !url.starts_with("tcp://") {
+            return Ok(Self {
+                alias: None,
+                url,
+                tls: None,
+                probe: false,
+            });
+        }
+
+        let flags_given =
+            args.tls_cert.is_some() || args.tls_key.is_some() || args.tls_ca.is_some();
+        if env_var("DOCKER_TLS_VERIFY").is_none() && !args.tls && !flags_given {
+            return Ok(Self {
+                alias: None,
+                url,
+                tls: None,
+                probe: false,
+            });
+        }
+
+        let cert_dir = env_var("DOCKER_CERT_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| config_dir.to_path_buf());
+        let ca = cert_dir.join("ca.pem");
+        let tls = TlsFiles {
+            cert: args
+                .tls_cert
+                .clone()
+                .unwrap_or_else(|| cert_dir.join("cert.pem")),
+            key: args
+                .tls_key
+                .clone()
+                .unwrap_or_else(|| cert_dir.join("key.pem")),
+            ca: args.tls_ca.clone().or_else(|| ca.exists().then_some(ca)),
+        };
+        tls.check()?;
+        Ok(Self {
+            alias: None,
+            url,
+            tls: Some(tls),
+            probe: false,
+        })
+    }
+
+    /// A named context stored under `<config>/contexts`.
+    fn from_context(name: &str, args: &ConnectionArgs, config_dir: &Path) -> Result<Self> {
+        let contexts = config_dir.join("contexts");
+        let (dir_name, meta) = find_context(&contexts.join("meta"), name)?;
+        let endpoint = meta
+            .endpoints
+            .docker
+            .ok_or_else(|| Error::Config(format!("context '{}' has no docker endpoint", name)))?;
+        let url = endpoint.host.unwrap_or_else(|| DEFAULT_URL.to_string());
+
+        // The TLS material of a context lives in a directory named like its metadata
+        let tls_dir = contexts.join("tls").join(&dir_name).join("docker");
+        if !url.starts_with("tcp://") || !tls_dir.join("cert.pem").exists() {
+            return Self::from_url(url, args, config_dir);
+        }
+        // docker-api always verifies the daemon, it cannot honour the context
+        if endpoint.skip_tls_verify {
+            return Err(Error::InvalidArgument(format!(
+                "context '{}' skips TLS verification, which dockerlogs does not support",
+                name
+            )));
+        }
+        let ca = tls_dir.join("ca.pem");
+        let tls = TlsFiles {
+            cert: args
+                .tls_cert
+                .clone()
+                .unwrap_or_else(|| tls_dir.join("cert.pem")),
+            key: args
+                .tls_key
+                .clone()
+                .unwrap_or_else(|| tls_dir.join("key.pem")),
+            ca: args.tls_ca.clone().or_else(|| ca.exists().then_some(ca)),
+        };
+        tls.check()?;
+        Ok(Self {
+            alias: None,
+            url,
+            tls: Some(tls),
+            probe: false,
+        })
+    }
+}
+
+impl TlsFiles {
+    /// A client for `url` (`tcp://host:port`) presenting these files.
+    /// docker-api reads `cert.pem`, `key.pem` and `ca.pem` from a single
+    /// directory, so files named otherwise are copied into a private
+    /// temporary one, which is no longer needed once they are loaded.
+    /// Without a CA, the system bundle takes its place, as the vendored
+    /// OpenSSL does not know where the system keeps it.
+    pub fn connect(&self, url: &str) -> Result<Docker> {
+        let host = url.strip_prefix("tcp://").unwrap_or(url);
+        let failed = |err: &dyn std::fmt::Display| {
+            Error::Config(format!("cannot set up TLS for {}: {}", url, err))
+        };
+        let ca = self.ca.clone().or_else(|| openssl_probe::probe().cert_file);
+        if let Some(dir) = self.common_dir(ca.as_deref()) {
+            return Docker::tls(host, dir, ca.is_some()).map_err(|err| failed(&err));
+        }
+
+        let staged = stage_dir().map_err(|err| failed(&err))?;
+        let copied = [
+            (Some(&self.cert), "cert.pem"),
+            (Some(&self.key), "key.pem"),
+            (ca.as_ref(), "ca.pem"),
+        ]
+        .into_iter()
+        .filter_map(|(file, name)| Some((file?, name)))
+        .try_for_each(|(file, name)| fs::copy(file, staged.join(name)).map(drop));
+        let docker = match copied {
+            Ok(()) => Docker::tls(host, &staged, ca.is_some()).map_err(|err| failed(&err)),
+            Err(err) => Err(failed(&err)),
+        };
+        let _ = fs::remove_dir_all(&staged);
+        docker
+    }
+
+    /// The directory holding the files and `ca` under the names docker-api
+    /// expects.
+    fn common_dir(&self, ca: Option<&Path>) -> Option<&Path> {
+        let dir = self.cert.parent()?;
+        let standard = self.cert == dir.join("cert.pem")
+            && self.key == dir.join("key.pem")
+            && ca.is_none_or(|ca| ca == dir.join("ca.pem"));
+        standard.then_some(dir)
+    }
+
+    fn check(&self) -> Result<()> {
+        let files = [Some(&self.cert), Some(&self.key), self.ca.as_ref()];
+        for file in files.into_iter().flatten() {
+            if !file.is_file() {
+                return Err(Error::Config(format!(
+                    "TLS file {} does not exist",
+                    file.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct ContextMeta {
+    name: String,
+    #[serde(default)]
+    endpoints: ContextEndpoints,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct ContextEndpoints {
+    docker: Option<DockerEndpoint>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct DockerEndpoint {
+    host: Option<String>,
+    #[serde(default, rename = "SkipTLSVerify")]
+    skip_tls_verify: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CliConfig {
+    current_context: Option<String>,
+}
+
+/// `$DOCKER_CONFIG`, or `~/.docker`.
+fn config_dir() -> PathBuf {
+    if let Some(dir) = env_var("DOCKER_CONFIG") {
+        return PathBuf::from(dir);
+    }
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".docker")
+}
+
+fn current_context(config_dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(config_dir.join("config.json")).ok()?;
+    serde_json::from_str::<CliConfig>(&content)
+        .ok()?
+        .current_context
+        .filter(|name| !name.is_empty())
+}
+
+/// Finds a context by name. Metadata directories are named after the
+/// SHA-256 of the context name, so they are scanned rather than computed.
+fn find_context(meta_dir: &Path, name: &str) -> Result<(String, ContextMeta)> {
+    let not_found = || Error::Config(format!("context '{}' not found", name));
+    let entries = fs::read_dir(meta_dir).map_err(|_| not_found())?;
+    for entry in entries.flatten() {
+        let content = match fs::read_to_string(entry.path().join("meta.json")) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let meta: ContextMeta = match serde_json::from_str(&content) {
+            Ok(meta) => meta,
+            Err(_) => continue,
+        };
+        if meta.name == name {
+            return Ok((entry.file_name().to_string_lossy().into_owned(), meta));
+        }
+    }
+    Err(not_found())
+}
+
+/// Splits `ALIAS=URL` host specs, plain URLs have no alias.
+fn parse_host_spec(spec: &str) -> Result<(Option<&str>, &str)> {
+    match spec.split_once('=') {
+        Some((alias, url)) if !alias.contains("://") => {
+            let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.';
+            if alias.is_empty() || !alias.chars().all(valid) {
+                return Err(Error::Config(format!(
+                    "invalid host alias '{}', use letters, digits, '-' or '.'",
+                    alias
+                )));
+            }
+            Ok((Some(alias), url))
+        }
+        _ => Ok((None, spec)),
+    }
+}
+
+/// Names endpoints without an alias after their host or socket file,
+/// falling back to their position when that name is already taken.
+fn assign_aliases(endpoints: &mut [Endpoint]) {
+    let mut taken: Vec<String> = endpoints.iter().filter_map(|e| e.alias.clone()).collect();
+    for (index, endpoint) in endpoints.iter_mut().enumerate() {
+        if endpoint.alias.is_some() {
+            continue;
+        }
+        let derived = derive_alias(&endpoint.url);
+        let alias = match derived {
+            Some(alias) if !taken.contains(&alias) => alias,
+            _ => format!("host{}", index + 1),
+        };
+        taken.push(alias.clone());
+        endpoint.alias = Some(alias);
+    }
+}
+
+fn derive_alias(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    let name = match scheme {
+        "unix" => Path::new(rest).file_stem()?.to_str()?.to_string(),
+        _ => rest.split([':', '/']).next()?.to_string(),
+    };
+    (!name.is_empty()).then_some(name)
+}
+
+/// Well-known daemon sockets, in order of preference: the system daemon,
+/// rootless Docker, Podman (user, then system), then desktop VMs.
+pub fn socket_candidates(runtime_dir: Option<&Path>, home: Option<&Path>) -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::from(DEFAULT_SOCKET)];
+    if let Some(runtime_dir) = runtime_dir {
+        candidates.push(runtime_dir.join("docker.sock"));
+        candidates.push(runtime_dir.join("podman").join("podman.sock"));
+    }
+    candidates.push(PathBuf::from("/run/podman/podman.sock"));
+    if let Some(home) = home {
+        candidates.push(home.join(".colima").join("default").join("docker.sock"));
+        candidates.push(home.join(".colima").join("docker.sock"));
+        candidates.push(home.join(".docker").join("run").join("docker.sock"));
+        candidates.push(home.join(".rd").join("docker.sock"));
+        candidates.push(
+            home.join(".local")
+                .join("share")
+                .join("containers")
+                .join("podman")
+                .join("machine")
+                .join("podman.sock"),
+        );
+    }
+    candidates
+}
+
+#[cfg(unix)]
+fn owner_uid(metadata: &fs::Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    metadata.uid().to_string()
+}
+
+#[cfg(not(unix))]
+fn owner_uid(_metadata: &fs::Metadata) -> String {
+    String::new()
+}
+
+async fn ping(url: &str) -> bool {
+    let docker = match Docker::new(url) {
+        Ok(docker) => docker,
+        Err(_) => return false,
+    };
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, Backend::ping(&docker)).await,
+        Ok(Ok(()))
+    )
+}
+
+/// A new directory only the current user can read, for staging key files.
+fn stage_dir() -> std::io::Result<PathBuf> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = env::temp_dir().join(format!(
+        "dockerlogs-tls-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
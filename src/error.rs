@@ -0,0 +1,259 @@
+use colored::*;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// A failure that ends the program or a container's log stream.
+#[derive(Debug)]
+pub enum Error {
+    /// The `--url` value cannot be used to reach a daemon
+    InvalidUrl {
+        url: String,
+        source: docker_api::Error,
+    },
+    /// Nothing answers on the socket or address
+    Connection {
+        url: String,
+        source: docker_api::Error,
+    },
+    /// The socket exists but the current user may not open it
+    PermissionDenied { url: String },
+    /// The daemon rejected the API version of the request
+    ApiVersion { message: String },
+    /// The container's logging driver cannot be read back through the API
+    UnsupportedLogDriver { container: String },
+    /// Any other error returned by the daemon or the client
+    Docker(docker_api::Error),
+    /// A command line value was rejected after parsing
+    InvalidArgument(String),
+    /// The endpoint configuration (context, certificates) is unusable
+    Config(String),
+    /// The TUI failed to drive the terminal
+    Terminal(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Classifies a Docker client error, `url` being the daemon address in use.
+    pub fn from_docker(err: docker_api::Error, url: &str) -> Self {
+        if let docker_api::Error::Fault { message, .. } = &err {
+            if is_api_version_message(message) {
+                return Error::ApiVersion {
+                    message: message.clone(),
+                };
+            }
+            return Error::Docker(err);
+        }
+
+        match io_error_kind(&err) {
+            Some(io::ErrorKind::PermissionDenied) => Error::PermissionDenied {
+                url: url.to_string(),
+            },
+            Some(
+                io::ErrorKind::NotFound
+                | io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::TimedOut,
+            ) => Error::Connection {
+                url: url.to_string(),
+                source: err,
+            },
+            _ if matches!(err, docker_api::Error::Hyper(_)) => Error::Connection {
+                url: url.to_string(),
+                source: err,
+            },
+            _ => Error::Docker(err),
+        }
+    }
+
+    /// Classifies an error returned while reading a container's logs.
+    pub fn from_logs(err: docker_api::Error, container: &str) -> Self {
+        match &err {
+            docker_api::Error::Fault { message, .. }
+                if message.contains("does not support reading") =>
+            {
+                Error::UnsupportedLogDriver {
+                    container: container.to_string(),
+                }
+            }
+            _ => Error::Docker(err),
+        }
+    }
+
+    /// A suggestion printed below the message.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Error::InvalidUrl { .. } => {
+                Some("use unix:///path/to/docker.sock, tcp://host:port or http://host:port")
+            }
+            Error::Connection { .. } => {
+                Some("is the Docker daemon running? Check the address given with --url")
+            }
+            Error::PermissionDenied { .. } => Some(
+                "add your user to the docker group (sudo usermod -aG docker $USER) and log in again",
+            ),
+            Error::ApiVersion { .. } => {
+                Some("upgrade the Docker daemon, or this tool if the daemon is newer")
+            }
+            Error::UnsupportedLogDriver { .. } => {
+                Some("use the json-file, local or journald logging driver, or enable dual logging")
+            }
+            Error::Config(_) => Some(
+                "check --url, --hosts-file, --context (see `docker context ls`) and the --tls-* paths",
+            ),
+            _ => None,
+        }
+    }
+
+    /// Process exit code, distinct per kind of failure.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Docker(_) | Error::Terminal(_) => 1,
+            Error::InvalidUrl { .. } | Error::InvalidArgument(_) | Error::Config(_) => 2,
+            Error::Connection { .. } => 3,
+            Error::PermissionDenied { .. } => 4,
+            Error::ApiVersion { .. } => 5,
+            Error::UnsupportedLogDriver { .. } => 6,
+        }
+    }
+
+    /// The full message for stderr, with the hint and, when verbose, the
+    /// chain of underlying causes.
+    pub fn report(&self) -> String {
+        let mut report = format!("{} {}", "error:".bright_red().bold(), self);
+        if let Some(hint) = self.hint() {
+            report.push_str(&format!("\n  {} {}", "hint:".bright_cyan(), hint));
+        }
+        if verbose() {
+            let mut source = std::error::Error::source(self);
+            while let Some(cause) = source {
+                report.push_str(&format!("\n  caused by: {}", cause));
+                source = cause.source();
+            }
+        }
+        report
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidUrl { url, .. } => write!(f, "invalid Docker URL '{}'", url),
+            Error::Connection { url, .. } => {
+                write!(f, "cannot connect to the Docker daemon at {}", url)
+            }
+            Error::PermissionDenied { url } => {
+                write!(
+                    f,
+                    "permission denied while connecting to the Docker daemon at {}",
+                    url
+                )
+            }
+            Error::ApiVersion { message } => write!(f, "Docker API version mismatch: {}", message),
+            Error::UnsupportedLogDriver { container } => write!(
+                f,
+                "the logging driver of container {} does not support reading logs",
+                container
+            ),
+            Error::Docker(err) => write!(f, "{}", err),
+            Error::InvalidArgument(message) | Error::Config(message) => write!(f, "{}", message),
+            Error::Terminal(err) => write!(f, "terminal error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidUrl { source, .. } | Error::Connection { source, .. } => Some(source),
+            Error::Docker(err) => Some(err),
+            Error::Terminal(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<regex::Error> for Error {
+    fn from(err: regex::Error) -> Self {
+        Error::InvalidArgument(format!("invalid regex: {}", err))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Terminal(err)
+    }
+}
+
+fn is_api_version_message(message: &str) -> bool {
+    message.contains("client version") || message.contains("API version")
+}
+
+/// Finds the I/O error at the root of a connection failure, if any.
+fn io_error_kind(err: &docker_api::Error) -> Option<io::ErrorKind> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(cause) = source {
+        if let Some(io_err) = cause.downcast_ref::<io::Error>() {
+            return Some(io_err.kind());
+        }
+        source = cause.source();
+    }
+    None
+}
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Warnings held back while the TUI owns the terminal.
+static CAPTURED: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Reports an error that is otherwise recovered from, only with `--verbose`.
+pub fn warn(context: &str, err: &dyn fmt::Display) {
+    if !verbose() {
+        return;
+    }
+    let message = format!("{}: {}", context, err);
+    if let Some(captured) = CAPTURED.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        captured.push(message);
+        return;
+    }
+    eprintln!(">>> {} {}", "!".bright_yellow(), message.dimmed());
+}
+
+/// Reports what the program decided on its own, only with `--verbose`.
+/// Captured along with the warnings.
+pub fn note(message: &str) {
+    if !verbose() {
+        return;
+    }
+    if let Some(captured) = CAPTURED.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        captured.push(message.to_string());
+        return;
+    }
+    eprintln!(">>> {} {}", "i".bright_blue(), message.dimmed());
+}
+
+/// Keeps warnings and notes in memory instead of writing them to stderr.
+pub fn capture_warnings() {
+    *CAPTURED.lock().unwrap_or_else(|e| e.into_inner()) = Some(Vec::new());
+}
+
+/// Returns the warnings and notes captured since the last call.
+pub fn take_warnings() -> Vec<String> {
+    CAPTURED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .map(std::mem::take)
+        .unwrap_or_default()
+}
@@ -0,0 +1,771 @@
+use crate::backend::Backend;
+use crate::lines::Stream;
+use chrono::{DateTime, SecondsFormat, Utc};
+use docker_api::conn::TtyChunk;
+use docker_api::models::{ContainerInspect200Response, ContainerSummary, EventMessage};
+use docker_api::opts::{ContainerListOpts, EventsOpts, LogsOpts};
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
+use regex::Regex;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// A container held by [`FakeBackend`].
+#[derive(Debug, Clone)]
+pub struct FakeContainer {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    pub labels: HashMap<String, String>,
+    pub running: bool,
+    pub exit_code: isize,
+    pub lines: Vec<FakeLine>,
+}
+
+/// A log line of a [`FakeContainer`].
+#[derive(Debug, Clone)]
+pub struct FakeLine {
+    pub stream: Stream,
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+}
+
+impl FakeContainer {
+    /// A running container with no logs yet.
+    pub fn new(id: &str, name: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            image: "busybox".to_string(),
+            labels: HashMap::new(),
+            running: true,
+            exit_code: 0,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn image(mut self, image: &str) -> Self {
+        self.image = image.to_string();
+        self
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Marks the container as exited with `exit_code`.
+    pub fn exited(mut self, exit_code: isize) -> Self {
+        self.running = false;
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Appends a line written now.
+    pub fn line(mut self, stream: Stream, message: &str) -> Self {
+        self.push_line(stream, message);
+        self
+    }
+
+    /// Appends a line written at `timestamp`.
+    pub fn line_at(mut self, stream: Stream, timestamp: DateTime<Utc>, message: &str) -> Self {
+        self.lines.push(FakeLine {
+            stream,
+            timestamp,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    fn push_line(&mut self, stream: Stream, message: &str) {
+        // Keep timestamps strictly increasing, as resuming relies on them
+        let now = Utc::now();
+        let timestamp = match self.lines.last() {
+            Some(last) if last.timestamp >= now => {
+                last.timestamp + chrono::Duration::nanoseconds(1)
+            }
+            _ => now,
+        };
+        self.lines.push(FakeLine {
+            stream,
+            timestamp,
+            message: message.to_string(),
+        });
+    }
+
+    fn status(&self) -> &'static str {
+        if self.running {
+            "running"
+        } else {
+            "exited"
+        }
+    }
+
+    /// Whether the container passes the `label`, `status` and `name`
+    /// filters of a list query, the way Docker applies them.
+    pub fn matches(&self, query: &Query) -> bool {
+        let labels = query
+            .filters("label")
+            .iter()
+            .all(|label| match label.split_once('=') {
+                Some((key, value)) => self.labels.get(key).is_some_and(|v| v == value),
+                None => self.labels.contains_key(label),
+            });
+        let statuses = query.filters("status");
+        let status = statuses.is_empty() || statuses.iter().any(|s| s == self.status());
+        let names = query.filters("name");
+        let name = names.is_empty()
+            || names.iter().any(|pattern| {
+                // Docker tries the name with and without its leading slash
+                Regex::new(pattern).is_ok_and(|re| {
+                    re.is_match(&self.name) || re.is_match(&format!("/{}", self.name))
+                })
+            });
+        labels && status && name
+    }
+
+    /// The `/containers/json` entry of the container.
+    pub fn summary(&self) -> serde_json::Value {
+        json!({
+            "Id": self.id,
+            "Names": [format!("/{}", self.name)],
+            "Image": self.image,
+            "Labels": self.labels,
+            "State": self.status(),
+        })
+    }
+
+    /// The `/containers/{id}/json` document of the container.
+    pub fn inspect(&self) -> serde_json::Value {
+        json!({
+            "Id": self.id,
+            "Name": format!("/{}", self.name),
+            "Config": {
+                "Image": self.image,
+                "Labels": self.labels,
+            },
+            "State": {
+                "Status": self.status(),
+                "Running": self.running,
+                "ExitCode": self.exit_code,
+            },
+        })
+    }
+
+    /// The container's start event, emitted at `time`.
+    pub fn start_event(&self, time: DateTime<Utc>) -> serde_json::Value {
+        let mut attributes = self.labels.clone();
+        attributes.insert("name".to_string(), self.name.clone());
+        attributes.insert("image".to_string(), self.image.clone());
+        json!({
+            "Type": "container",
+            "Action": "start",
+            "Actor": {
+                "ID": self.id,
+                "Attributes": attributes,
+            },
+            "time": time.timestamp(),
+            "timeNano": time.timestamp_nanos_opt(),
+        })
+    }
+}
+
+impl FakeLine {
+    /// The line as Docker frames it, with the timestamp prefix when asked for.
+    pub fn to_bytes(&self, timestamps: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if timestamps {
+            bytes.extend_from_slice(
+                self.timestamp
+                    .to_rfc3339_opts(SecondsFormat::Nanos, true)
+                    .as_bytes(),
+            );
+            bytes.push(b' ');
+        }
+        bytes.extend_from_slice(self.message.as_bytes());
+        bytes.push(b'\n');
+        bytes
+    }
+}
+
+/// The query parameters of a request, as Docker reads them.
+#[derive(Debug, Clone, Default)]
+pub struct Query(HashMap<String, String>);
+
+impl Query {
+    pub fn parse(query: Option<&str>) -> Self {
+        let pairs = query
+            .unwrap_or_default()
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.to_string(), decode(value)))
+            .collect();
+        Self(pairs)
+    }
+
+    /// The values given for `key` in the JSON `filters` parameter.
+    pub fn filters(&self, key: &str) -> Vec<String> {
+        let mut filters: HashMap<String, Vec<String>> = self
+            .0
+            .get("filters")
+            .and_then(|filters| serde_json::from_str(filters).ok())
+            .unwrap_or_default();
+        filters.remove(key).unwrap_or_default()
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        matches!(self.0.get(key).map(String::as_str), Some("true" | "1"))
+    }
+
+    /// Whether a stream was requested; Docker sends neither by default.
+    pub fn wants(&self, stream: Stream) -> bool {
+        match stream {
+            Stream::StdIn => false,
+            Stream::StdOut => self.flag("stdout"),
+            Stream::StdErr => self.flag("stderr"),
+        }
+    }
+
+    /// How many trailing lines to send, `None` for all of them.
+    pub fn tail(&self) -> Option<usize> {
+        self.0.get("tail").and_then(|tail| tail.parse().ok())
+    }
+
+    /// Lines older than this are skipped.
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        self.time("since")
+    }
+
+    /// Lines newer than this are skipped, and a followed stream ends once
+    /// this time has passed.
+    pub fn until(&self) -> Option<DateTime<Utc>> {
+        self.time("until")
+    }
+
+    fn time(&self, key: &str) -> Option<DateTime<Utc>> {
+        let time: f64 = self.0.get(key)?.parse().ok()?;
+        DateTime::from_timestamp(time.trunc() as i64, (time.fract() * 1e9) as u32)
+    }
+}
+
+/// How many times each Docker API endpoint was called.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApiCalls {
+    pub list: usize,
+    pub inspect: usize,
+    pub logs: usize,
+    pub events: usize,
+}
+
+/// An open stream was cut by [`FakeBackend::disconnect`] or
+/// [`FakeBackend::drop_streams`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dropped;
+
+#[derive(Debug, Default)]
+struct FakeState {
+    containers: Vec<FakeContainer>,
+    events: Vec<serde_json::Value>,
+    /// Open event streams
+    subscribers: usize,
+    calls: ApiCalls,
+    /// Every request is refused until the daemon is restored
+    down: bool,
+    /// Bumped whenever open streams are dropped, streams opened before are cut
+    connection: usize,
+    /// The next log line sent is cut short and its connection dropped
+    tear: bool,
+    /// Started once the next listing is taken, before it is answered
+    start_during_list: Option<FakeContainer>,
+}
+
+impl FakeState {
+    fn find(&self, id: &str) -> Option<&FakeContainer> {
+        self.containers.iter().find(|c| c.id == id)
+    }
+}
+
+/// An in-memory Docker daemon for tests.
+///
+/// The test adds, starts and stops containers and appends to their logs;
+/// followed log streams and the events stream see every change as it
+/// happens.
+#[derive(Debug, Clone)]
+pub struct FakeBackend {
+    state: Arc<Mutex<FakeState>>,
+    /// Bumped on every change so open streams look at the state again
+    changed: Arc<watch::Sender<()>>,
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::default(),
+            changed: Arc::new(watch::Sender::new(())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update<T>(&self, change: impl FnOnce(&mut FakeState) -> T) -> T {
+        let result = change(&mut self.lock());
+        self.changed.send_replace(());
+        result
+    }
+
+    /// Adds a container that already existed, without any event.
+    pub fn add(&self, container: FakeContainer) {
+        self.update(|state| {
+            state.containers.retain(|c| c.id != container.id);
+            state.containers.push(container);
+        });
+    }
+
+    /// Starts a container, emitting its start event.
+    pub fn start(&self, mut container: FakeContainer) {
+        container.running = true;
+        self.update(|state| {
+            // Keep event times strictly increasing, as resuming relies on them
+            let now = Utc::now();
+            let time = match state.events.last().and_then(|e| e["timeNano"].as_i64()) {
+                Some(last) if last >= now.timestamp_nanos_opt().unwrap_or(i64::MAX) => {
+                    DateTime::from_timestamp_nanos(last + 1)
+                }
+                _ => now,
+            };
+            state.events.push(container.start_event(time));
+            state.containers.retain(|c| c.id != container.id);
+            state.containers.push(container);
+        });
+    }
+
+    /// Appends a line to a container's logs.
+    pub fn log(&self, id: &str, stream: Stream, message: &str) {
+        self.update(|state| {
+            if let Some(container) = state.containers.iter_mut().find(|c| c.id == id) {
+                container.push_line(stream, message);
+            }
+        });
+    }
+
+    /// Stops a container, ending its followed log streams.
+    pub fn stop(&self, id: &str, exit_code: isize) {
+        self.update(|state| {
+            if let Some(container) = state.containers.iter_mut().find(|c| c.id == id) {
+                container.running = false;
+                container.exit_code = exit_code;
+            }
+        });
+    }
+
+    /// Removes a container altogether.
+    pub fn remove(&self, id: &str) {
+        self.update(|state| state.containers.retain(|c| c.id != id));
+    }
+
+    /// Cuts every open log and event stream, as a dropped connection would.
+    /// The daemon keeps answering new requests.
+    pub fn drop_streams(&self) {
+        self.update(|state| state.connection += 1);
+    }
+
+    /// Sends only the first half of the next log line written to an open
+    /// stream, then drops every open stream.
+    pub fn tear_next_line(&self) {
+        self.update(|state| state.tear = true);
+    }
+
+    /// Starts `container` right after the next listing is taken, and
+    /// answers that listing only once the start event had time to be
+    /// handled, as when a container starts while Docker sends a listing.
+    pub fn start_during_next_list(&self, container: FakeContainer) {
+        self.update(|state| state.start_during_list = Some(container));
+    }
+
+    /// Takes the daemon down: open streams are cut and every request is
+    /// refused until [`restore`](Self::restore). Containers can still be
+    /// started and logged to in the meantime.
+    pub fn disconnect(&self) {
+        self.update(|state| {
+            state.connection += 1;
+            state.down = true;
+        });
+    }
+
+    /// Brings the daemon back after [`disconnect`](Self::disconnect).
+    pub fn restore(&self) {
+        self.update(|state| state.down = false);
+    }
+
+    /// Whether requests are refused.
+    pub fn is_down(&self) -> bool {
+        self.lock().down
+    }
+
+    /// Waits until someone follows the events, so that containers started
+    /// afterwards are not missed.
+    pub async fn subscribed(&self) {
+        let mut changed = self.changed.subscribe();
+        while self.lock().subscribers == 0 {
+            if changed.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// The API calls made so far.
+    pub fn calls(&self) -> ApiCalls {
+        self.lock().calls
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.lock().find(id).is_some()
+    }
+
+    /// The `/containers/json` response.
+    pub fn list_json(&self, query: &Query) -> serde_json::Value {
+        let mut state = self.lock();
+        state.calls.list += 1;
+        // Like Docker, a status filter lists stopped containers without `all`
+        let all = query.flag("all") || !query.filters("status").is_empty();
+        let containers: Vec<_> = state
+            .containers
+            .iter()
+            .filter(|c| (c.running || all) && c.matches(query))
+            .map(FakeContainer::summary)
+            .collect();
+        json!(containers)
+    }
+
+    /// The `/containers/{id}/json` response, `None` for an unknown container.
+    pub fn inspect_json(&self, id: &str) -> Option<serde_json::Value> {
+        let mut state = self.lock();
+        state.calls.inspect += 1;
+        state.find(id).map(FakeContainer::inspect)
+    }
+
+    /// The log frames of a container selected by a `/containers/{id}/logs`
+    /// query, followed as long as the container runs when asked to.
+    pub fn log_frames(
+        &self,
+        id: &str,
+        query: Query,
+    ) -> BoxStream<'static, Result<TtyChunk, Dropped>> {
+        let connection = self.update(|state| {
+            state.calls.logs += 1;
+            state.connection
+        });
+        let cursor = LogCursor {
+            backend: self.clone(),
+            changed: self.changed.subscribe(),
+            connection: Some(connection),
+            id: id.to_string(),
+            query,
+            next: None,
+        };
+        futures::stream::unfold(cursor, |mut cursor| async move {
+            let frame = cursor.next_frame().await?;
+            Some((frame, cursor))
+        })
+        .boxed()
+    }
+
+    /// The container events selected by an `/events` query: the ones
+    /// emitted from `since` on, or from now on, that pass its filters.
+    pub fn event_stream(
+        &self,
+        query: &Query,
+    ) -> BoxStream<'static, Result<serde_json::Value, Dropped>> {
+        let since = query.since().map(|since| since.timestamp());
+        let (next, connection) = self.update(|state| {
+            state.calls.events += 1;
+            state.subscribers += 1;
+            let next = match since {
+                Some(since) => state
+                    .events
+                    .iter()
+                    .position(|event| event["time"].as_i64() >= Some(since))
+                    .unwrap_or(state.events.len()),
+                None => state.events.len(),
+            };
+            (next, state.connection)
+        });
+        let cursor = EventCursor {
+            backend: self.clone(),
+            changed: self.changed.subscribe(),
+            connection: Some(connection),
+            query: query.clone(),
+            next,
+        };
+        futures::stream::unfold(cursor, |mut cursor| async move {
+            let event = cursor.next_event().await?;
+            Some((event, cursor))
+        })
+        .boxed()
+    }
+}
+
+struct LogCursor {
+    backend: FakeBackend,
+    changed: watch::Receiver<()>,
+    /// The connection the stream was opened on, `None` once it was cut
+    connection: Option<usize>,
+    id: String,
+    query: Query,
+    /// Index of the next line to look at, set on the first read
+    next: Option<usize>,
+}
+
+impl LogCursor {
+    async fn next_frame(&mut self) -> Option<Result<TtyChunk, Dropped>> {
+        loop {
+            self.changed.borrow_and_update();
+            {
+                let mut state = self.backend.lock();
+                if self.connection? != state.connection {
+                    self.connection = None;
+                    return Some(Err(Dropped));
+                }
+                // Removed while being followed
+                let container = state.find(&self.id)?;
+                let running = container.running;
+                let lines = &container.lines;
+                let next = self.next.get_or_insert_with(|| match self.query.tail() {
+                    Some(tail) => lines.len().saturating_sub(tail),
+                    None => 0,
+                });
+                let mut frame = None;
+                while let Some(line) = lines.get(*next) {
+                    *next += 1;
+                    let too_old = self
+                        .query
+                        .since()
+                        .is_some_and(|since| line.timestamp < since);
+                    let too_new = self
+                        .query
+                        .until()
+                        .is_some_and(|until| line.timestamp > until);
+                    if !too_old && !too_new && self.query.wants(line.stream) {
+                        frame = Some((line.stream, line.to_bytes(self.query.flag("timestamps"))));
+                        break;
+                    }
+                }
+
+                if let Some((stream, mut bytes)) = frame {
+                    if state.tear {
+                        state.tear = false;
+                        state.connection += 1;
+                        bytes.truncate(bytes.len() / 2);
+                        self.backend.changed.send_replace(());
+                    }
+                    return Some(Ok(match stream {
+                        Stream::StdIn => TtyChunk::StdIn(bytes),
+                        Stream::StdOut => TtyChunk::StdOut(bytes),
+                        Stream::StdErr => TtyChunk::StdErr(bytes),
+                    }));
+                }
+                if !self.query.flag("follow") || !running {
+                    return None;
+                }
+            }
+            let left = match self.query.until() {
+                Some(until) => match (until - Utc::now()).to_std() {
+                    Ok(left) => left,
+                    Err(_) => return None,
+                },
+                None => Duration::MAX,
+            };
+            match tokio::time::timeout(left, self.changed.changed()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) | Err(_) => return None,
+            }
+        }
+    }
+}
+
+struct EventCursor {
+    backend: FakeBackend,
+    changed: watch::Receiver<()>,
+    /// The connection the stream was opened on, `None` once it was cut
+    connection: Option<usize>,
+    query: Query,
+    next: usize,
+}
+
+impl EventCursor {
+    async fn next_event(&mut self) -> Option<Result<serde_json::Value, Dropped>> {
+        loop {
+            self.changed.borrow_and_update();
+            {
+                let state = self.backend.lock();
+                if self.connection? != state.connection {
+                    self.connection = None;
+                    return Some(Err(Dropped));
+                }
+                while let Some(event) = state.events.get(self.next) {
+                    self.next += 1;
+                    if event_matches(event, &self.query) {
+                        return Some(Ok(event.clone()));
+                    }
+                }
+            }
+            if self.changed.changed().await.is_err() {
+                return None;
+            }
+        }
+    }
+}
+
+impl Drop for EventCursor {
+    fn drop(&mut self) {
+        self.backend.lock().subscribers -= 1;
+    }
+}
+
+/// Whether an event passes the `type`, `event` and `label` filters of an
+/// `/events` query, the way Docker applies them.
+fn event_matches(event: &serde_json::Value, query: &Query) -> bool {
+    let any_of = |key: &str, value: &serde_json::Value| {
+        let wanted = query.filters(key);
+        wanted.is_empty() || wanted.iter().any(|w| value.as_str() == Some(w))
+    };
+    let attributes = &event["Actor"]["Attributes"];
+    let labels = query
+        .filters("label")
+        .iter()
+        .all(|label| match label.split_once('=') {
+            Some((key, value)) => attributes[key].as_str() == Some(value),
+            None => !attributes[label.as_str()].is_null(),
+        });
+    any_of("type", &event["Type"]) && any_of("event", &event["Action"]) && labels
+}
+
+/// Undoes the form encoding of a query value.
+fn decode(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let escaped = tail
+                    .get(..2)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escaped {
+                    Some(escaped) => {
+                        bytes.push(escaped);
+                        rest = &tail[2..];
+                    }
+                    None => bytes.push(byte),
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// What the client sees of a dropped connection or a daemon that is down.
+fn connection_error(kind: io::ErrorKind) -> docker_api::Error {
+    docker_api::Error::IO(io::Error::new(kind, "fake daemon disconnected"))
+}
+
+fn no_such_container(id: &str) -> docker_api::Error {
+    docker_api::Error::Fault {
+        code: http::StatusCode::NOT_FOUND,
+        message: format!("No such container: {}", id),
+    }
+}
+
+impl Backend for FakeBackend {
+    fn list<'a>(
+        &'a self,
+        opts: &'a ContainerListOpts,
+    ) -> BoxFuture<'a, docker_api::Result<Vec<ContainerSummary>>> {
+        if self.is_down() {
+            return async { Err(connection_error(io::ErrorKind::ConnectionRefused)) }.boxed();
+        }
+        let list = self.list_json(&Query::parse(opts.serialize().as_deref()));
+        let started = self.lock().start_during_list.take();
+        async move {
+            if let Some(container) = started {
+                self.start(container);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            serde_json::from_value(list).map_err(docker_api::Error::from)
+        }
+        .boxed()
+    }
+
+    fn inspect<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, docker_api::Result<ContainerInspect200Response>> {
+        if self.is_down() {
+            return async { Err(connection_error(io::ErrorKind::ConnectionRefused)) }.boxed();
+        }
+        let info = self.inspect_json(id);
+        async move {
+            let info = info.ok_or_else(|| no_such_container(id))?;
+            serde_json::from_value(info).map_err(docker_api::Error::from)
+        }
+        .boxed()
+    }
+
+    fn logs<'a>(
+        &'a self,
+        id: &'a str,
+        opts: &LogsOpts,
+    ) -> BoxStream<'a, docker_api::Result<TtyChunk>> {
+        if self.is_down() || !self.contains(id) {
+            self.lock().calls.logs += 1;
+            let err = if self.is_down() {
+                connection_error(io::ErrorKind::ConnectionRefused)
+            } else {
+                no_such_container(id)
+            };
+            return futures::stream::once(async move { Err(err) }).boxed();
+        }
+        self.log_frames(id, Query::parse(opts.serialize().as_deref()))
+            .map(|frame| frame.map_err(|Dropped| connection_error(io::ErrorKind::ConnectionReset)))
+            .boxed()
+    }
+
+    fn events<'a>(&'a self, opts: &EventsOpts) -> BoxStream<'a, docker_api::Result<EventMessage>> {
+        if self.is_down() {
+            self.lock().calls.events += 1;
+            let err = connection_error(io::ErrorKind::ConnectionRefused);
+            return futures::stream::once(async move { Err(err) }).boxed();
+        }
+        // Only start events are ever emitted, which is all the sources ask for
+        self.event_stream(&Query::parse(opts.serialize().as_deref()))
+            .map(|event| match event {
+                Ok(event) => serde_json::from_value(event).map_err(docker_api::Error::from),
+                Err(Dropped) => Err(connection_error(io::ErrorKind::ConnectionReset)),
+            })
+            .boxed()
+    }
+
+    fn ping(&self) -> BoxFuture<'_, docker_api::Result<()>> {
+        let result = match self.is_down() {
+            true => Err(connection_error(io::ErrorKind::ConnectionRefused)),
+            false => Ok(()),
+        };
+        async move { result }.boxed()
+    }
+}
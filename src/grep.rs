@@ -0,0 +1,146 @@
+use colored::*;
+use regex::Regex;
+use std::collections::VecDeque;
+
+/// Line-content filter built from the `--grep` / `--exclude-grep` options.
+#[derive(Debug, Clone, Default)]
+pub struct LineFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    before_context: usize,
+    after_context: usize,
+}
+
+impl LineFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, regex::Error> {
+        Ok(Self {
+            include: include
+                .iter()
+                .map(|r| Regex::new(r))
+                .collect::<Result<_, _>>()?,
+            exclude: exclude
+                .iter()
+                .map(|r| Regex::new(r))
+                .collect::<Result<_, _>>()?,
+            before_context: 0,
+            after_context: 0,
+        })
+    }
+
+    /// Sets how many lines of context to show around each match.
+    pub fn with_context(mut self, before: usize, after: usize) -> Self {
+        self.before_context = before;
+        self.after_context = after;
+        self
+    }
+
+    /// Creates an empty context buffer for a single container stream.
+    pub fn context_buffer<T>(&self) -> ContextBuffer<T> {
+        ContextBuffer::new(self.before_context, self.after_context)
+    }
+
+    /// A line passes if it matches any include pattern (or there are none)
+    /// and matches no exclude pattern.
+    pub fn matches(&self, line: &str) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|r| r.is_match(line));
+        included && !self.exclude.iter().any(|r| r.is_match(line))
+    }
+
+    /// Returns the line with every include match highlighted.
+    pub fn highlight(&self, line: &str) -> String {
+        if self.include.is_empty() {
+            return line.to_string();
+        }
+
+        // Collect match ranges from all patterns and merge overlapping ones
+        let mut ranges: Vec<(usize, usize)> = self
+            .include
+            .iter()
+            .flat_map(|r| r.find_iter(line).map(|m| (m.start(), m.end())))
+            .filter(|(start, end)| start < end)
+            .collect();
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        let mut result = String::with_capacity(line.len());
+        let mut pos = 0;
+        for (start, end) in merged {
+            result.push_str(&line[pos..start]);
+            result.push_str(&line[start..end].bright_red().bold().to_string());
+            pos = end;
+        }
+        result.push_str(&line[pos..]);
+        result
+    }
+}
+
+/// A line to emit after passing through a [`ContextBuffer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextLine<T> {
+    /// A line that matched the filter
+    Match(T),
+    /// A line shown only as context around a match
+    Context(T),
+    /// Marks a gap between two non-contiguous groups
+    Separator,
+}
+
+/// Per-container ring buffer implementing grep-style `-A/-B/-C` context.
+#[derive(Debug)]
+pub struct ContextBuffer<T> {
+    before: usize,
+    after: usize,
+    pending: VecDeque<T>,
+    after_left: usize,
+    printed_any: bool,
+    gap: bool,
+}
+
+impl<T> ContextBuffer<T> {
+    pub fn new(before: usize, after: usize) -> Self {
+        Self {
+            before,
+            after,
+            pending: VecDeque::with_capacity(before),
+            after_left: 0,
+            printed_any: false,
+            gap: false,
+        }
+    }
+
+    /// Feeds one line through the buffer and returns what should be printed.
+    pub fn push(&mut self, line: T, matched: bool) -> Vec<ContextLine<T>> {
+        let mut out = Vec::new();
+
+        if matched {
+            // Separators only make sense when context was requested
+            let uses_context = self.before > 0 || self.after > 0;
+            if uses_context && self.printed_any && self.gap {
+                out.push(ContextLine::Separator);
+            }
+            out.extend(self.pending.drain(..).map(ContextLine::Context));
+            out.push(ContextLine::Match(line));
+            self.after_left = self.after;
+            self.printed_any = true;
+            self.gap = false;
+        } else if self.after_left > 0 {
+            self.after_left -= 1;
+            out.push(ContextLine::Context(line));
+        } else {
+            self.pending.push_back(line);
+            if self.pending.len() > self.before {
+                self.pending.pop_front();
+                self.gap = true;
+            }
+        }
+
+        out
+    }
+}
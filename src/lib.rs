@@ -0,0 +1,34 @@
+//! Tails the logs of many Docker containers at once.
+//!
+//! [`source::LogSource`] reads the containers a [`selection::Selector`]
+//! picks on the daemon behind an [`endpoint::Endpoint`], through a
+//! [`backend::Backend`], and turns them into a stream of
+//! [`source::SourceEvent`]s. [`lines`], [`timestamps`] and [`error`] hold
+//! the types these share.
+
+pub mod backend;
+pub mod containers;
+pub mod endpoint;
+pub mod error;
+#[cfg(feature = "testing")]
+pub mod fake;
+pub mod lines;
+mod reconnect;
+pub mod selection;
+pub mod source;
+pub mod timestamps;
+
+// How the dockerlogs binary renders events. Exported only for the binary
+// and the integration tests, these are not part of the library API.
+#[doc(hidden)]
+pub mod ansi;
+#[doc(hidden)]
+pub mod grep;
+#[doc(hidden)]
+pub mod merge;
+#[doc(hidden)]
+pub mod output;
+#[doc(hidden)]
+pub mod template;
+#[doc(hidden)]
+pub mod tui;
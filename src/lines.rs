@@ -0,0 +1,133 @@
+use crate::timestamps::split_timestamp;
+use chrono::{DateTime, Utc};
+use docker_api::conn::TtyChunk;
+use std::borrow::Cow;
+
+/// Which container stream a log line came from.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    StdIn,
+    StdOut,
+    StdErr,
+}
+
+impl Stream {
+    /// Short single-letter descriptor used in the text output.
+    pub fn descriptor(&self) -> &'static str {
+        match self {
+            Stream::StdIn => "i",
+            Stream::StdOut => "o",
+            Stream::StdErr => "e",
+        }
+    }
+
+    /// Full stream name as used in structured output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stream::StdIn => "stdin",
+            Stream::StdOut => "stdout",
+            Stream::StdErr => "stderr",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Stream::StdIn => 0,
+            Stream::StdOut => 1,
+            Stream::StdErr => 2,
+        }
+    }
+}
+
+/// A complete log line as read from a container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    pub stream: Stream,
+    /// Docker timestamp, only present when timestamps were requested
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The line as the container wrote it, without its newline
+    pub bytes: Vec<u8>,
+}
+
+impl LogLine {
+    /// The line as text, invalid UTF-8 replaced by U+FFFD.
+    pub fn message(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.bytes)
+    }
+}
+
+/// Reassembles complete lines out of `TtyChunk` payloads.
+///
+/// Docker does not guarantee that a chunk holds exactly one line: a chunk
+/// may contain several lines or end in the middle of one. Partial lines are
+/// buffered per stream until their newline arrives.
+#[derive(Debug, Default)]
+pub struct LineAssembler {
+    buffers: [Vec<u8>; 3],
+    timestamps: bool,
+}
+
+impl LineAssembler {
+    /// `timestamps` tells whether Docker was asked to prefix each line
+    /// with its timestamp, in which case the prefix is parsed out.
+    pub fn new(timestamps: bool) -> Self {
+        Self {
+            buffers: Default::default(),
+            timestamps,
+        }
+    }
+
+    /// Feeds a chunk and returns every line it completed.
+    pub fn push(&mut self, chunk: TtyChunk) -> Vec<LogLine> {
+        let (stream, data) = match chunk {
+            TtyChunk::StdIn(inner) => (Stream::StdIn, inner),
+            TtyChunk::StdOut(inner) => (Stream::StdOut, inner),
+            TtyChunk::StdErr(inner) => (Stream::StdErr, inner),
+        };
+
+        let timestamps = self.timestamps;
+        let buffer = &mut self.buffers[stream.index()];
+        buffer.extend_from_slice(&data);
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        while let Some(pos) = buffer[start..].iter().position(|&b| b == b'\n') {
+            let end = start + pos;
+            lines.push(decode_line(stream, &buffer[start..end], timestamps));
+            start = end + 1;
+        }
+        buffer.drain(..start);
+
+        lines
+    }
+
+    /// Flushes any trailing partial lines once the stream has ended.
+    pub fn finish(&mut self) -> Vec<LogLine> {
+        [Stream::StdIn, Stream::StdOut, Stream::StdErr]
+            .into_iter()
+            .filter_map(|stream| {
+                let buffer = std::mem::take(&mut self.buffers[stream.index()]);
+                if buffer.is_empty() {
+                    None
+                } else {
+                    Some(decode_line(stream, &buffer, self.timestamps))
+                }
+            })
+            .collect()
+    }
+}
+
+fn decode_line(stream: Stream, bytes: &[u8], timestamps: bool) -> LogLine {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    let (timestamp, bytes) = if timestamps {
+        split_timestamp(bytes)
+    } else {
+        (None, bytes)
+    };
+    LogLine {
+        stream,
+        timestamp,
+        bytes: bytes.to_vec(),
+    }
+}
@@ -1,40 +1,106 @@
 use clap::{Parser, Subcommand};
-use colored::*;
-use futures::StreamExt;
-use std::collections::HashSet;
+use dockerlogs::backend::Backend;
+use dockerlogs::endpoint::{ConnectionArgs, Endpoint};
+use dockerlogs::error::{self, Error};
+use dockerlogs::grep::{ContextBuffer, LineFilter};
+use dockerlogs::lines::LogLine;
+use dockerlogs::merge;
+use dockerlogs::output::{OutputFormat, Printer, TimedLine};
+use dockerlogs::selection::{SelectionArgs, Selector};
+use dockerlogs::source::{self, LogSource, SourceEvent, SourceOptions};
+use dockerlogs::template::Template;
+use dockerlogs::timestamps::TimeArgs;
+use dockerlogs::tui;
+use std::collections::HashMap;
+use std::process::ExitCode;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-
-mod tui;
-
-async fn get_docker(url: &str) -> docker_api::Docker {
-    docker_api::Docker::new(url).unwrap()
+use std::time::Duration;
+
+/// Connects to the endpoint, or to the first local daemon found when none
+/// was configured.
+async fn get_docker(endpoint: Endpoint) -> error::Result<(Arc<dyn Backend>, Endpoint)> {
+    let endpoint = endpoint.discover().await;
+    let docker = match &endpoint.tls {
+        Some(tls) => tls.connect(&endpoint.url)?,
+        None => docker_api::Docker::new(&endpoint.url).map_err(|source| Error::InvalidUrl {
+            url: endpoint.url.clone(),
+            source,
+        })?,
+    };
+    Ok((Arc::new(docker), endpoint))
 }
 
 const VERSION: &str = env!("BUILD_VERSION");
 
+// Flattened option structs carry `//` comments only, clap would take their
+// doc comments as the about text of the whole program
 #[derive(Parser, Debug)]
 #[clap(author, version = VERSION, about, long_about = None)]
 struct Args {
-    /// Docker socket URL
-    #[clap(default_value_t = String::from("unix:///var/run/docker.sock"), short, long, value_parser, global = true)]
-    url: String,
+    #[clap(flatten)]
+    connection: ConnectionArgs,
     /// Containers filter regex
     #[clap(default_value = ".*", short, long, value_parser, global = true)]
     container_regex: String,
+    /// Include stopped containers
+    #[clap(default_value_t = false, short, long, value_parser, global = true)]
+    all: bool,
+    #[clap(flatten)]
+    selection: SelectionArgs,
+    /// Seconds between container list resyncs in follow and TUI modes, 0 disables
+    #[clap(default_value_t = 30, long, value_parser, global = true)]
+    resync_interval: u64,
+    /// Report recovered errors such as dropped streams and failed inspects
+    #[clap(default_value_t = false, long, value_parser, global = true)]
+    verbose: bool,
 
     /// Follow docker logs (only for default logs mode)
     #[clap(default_value_t = false, short, long, value_parser)]
     follow: bool,
-    /// Show last n lines (only for default logs mode)
-    #[clap(default_value_t = 20, short = 'l', long, value_parser)]
-    last_n_lines: usize,
+    /// Show last n lines [default: 20, or every line of a --since/--until window] (only for default logs mode)
+    #[clap(short = 'l', long, value_parser)]
+    last_n_lines: Option<usize>,
     /// Hide stdout (only for default logs mode)
     #[clap(default_value_t = false, short = 'o', long, value_parser)]
     no_stdout: bool,
     /// Hide stderr (only for default logs mode)
     #[clap(default_value_t = false, short = 'e', long, value_parser)]
     no_stderr: bool,
+    /// Only show lines matching this regex, repeatable (only for default logs mode)
+    #[clap(short, long, value_parser)]
+    grep: Vec<String>,
+    /// Hide lines matching this regex, repeatable (only for default logs mode)
+    #[clap(short = 'v', long, value_parser)]
+    exclude_grep: Vec<String>,
+    /// Show n lines after each grep match (only for default logs mode)
+    #[clap(short = 'A', long, value_parser)]
+    after_context: Option<usize>,
+    /// Show n lines before each grep match (only for default logs mode)
+    #[clap(short = 'B', long, value_parser)]
+    before_context: Option<usize>,
+    /// Show n lines before and after each grep match (only for default logs mode)
+    #[clap(short = 'C', long, value_parser)]
+    context_lines: Option<usize>,
+    /// Merge output of all containers in chronological order (only without --follow)
+    #[clap(
+        default_value_t = false,
+        short,
+        long,
+        value_parser,
+        conflicts_with = "follow"
+    )]
+    sort: bool,
+    /// Output format (only for default logs mode)
+    #[clap(default_value = "text", long, value_enum)]
+    output: OutputFormat,
+    /// Line template, e.g. "{name:<20} {ts} {msg}" (only for text output)
+    #[clap(long, value_parser = Template::parse)]
+    format: Option<Template>,
+    /// Drop escape sequences and control characters from log lines (only for default logs mode)
+    #[clap(default_value_t = false, long, value_parser)]
+    sanitize: bool,
+    #[clap(flatten)]
+    time: TimeArgs,
 
     #[clap(subcommand)]
     command: Option<Command>,
@@ -44,261 +110,183 @@ struct Args {
 enum Command {
     /// Interactive TUI mode
     Tui {
-        /// Show last n lines
-        #[clap(default_value_t = 100, short, long, value_parser)]
-        last_n_lines: usize,
+        /// Show last n lines [default: 100, or every line of a --since/--until window]
+        #[clap(short, long, value_parser)]
+        last_n_lines: Option<usize>,
+        #[clap(flatten)]
+        time: TimeArgs,
     },
 }
 
-async fn start_logging_container(
-    docker_url: String,
-    container_id: String,
-    container_regex: regex::Regex,
-    log_opts: docker_api::opts::LogsOpts,
+/// Printing state of one watched container.
+struct ContainerOutput {
     color_index: usize,
-    watched_containers: Arc<Mutex<HashSet<String>>>,
-    follow: bool,
-) {
-    let docker = get_docker(&docker_url).await;
-    let container = docker_api::container::Container::new(docker, container_id.clone());
-
-    let info = match container.inspect().await {
-        Ok(info) => info,
-        Err(_) => {
-            watched_containers.lock().await.remove(&container_id);
-            return;
-        }
-    };
-
-    let name = match &info.name {
-        Some(n) => {
-            if let Some(stripped) = n.strip_prefix('/') {
-                stripped.to_owned()
-            } else {
-                n.clone()
-            }
-        }
-        None => {
-            watched_containers.lock().await.remove(&container_id);
-            return;
-        }
-    };
-    if container_regex.find(&name).is_none() {
-        watched_containers.lock().await.remove(&container_id);
-        return;
-    }
+    context: ContextBuffer<LogLine>,
+    /// In sort mode lines are collected with their timestamp and merged later
+    collected: Option<Vec<TimedLine>>,
+}
 
-    println!(
-        ">>> {} Started watching container {}",
-        "✓".bright_green(),
-        name.bright_cyan()
-    );
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Args::parse();
+    error::set_verbose(cli.verbose);
 
-    let mut stream = container.logs(&log_opts);
-    while let Some(data) = stream.next().await {
-        let colored_name = match color_index % 9 {
-            0 => name.bright_green().clone(),
-            1 => name.bright_blue(),
-            2 => name.bright_yellow(),
-            3 => name.bright_magenta(),
-            4 => name.bright_cyan(),
-            5 => name.bright_white(),
-            6 => name.bright_red(),
-            7 => name.yellow(),
-            8 => name.green(),
-            _ => name.on_black().white(),
-        };
-        match data {
-            Ok(contents) => {
-                let (descriptor, line) = match contents {
-                    docker_api::conn::TtyChunk::StdIn(inner) => {
-                        ("i", String::from_utf8_lossy(&inner).into_owned())
-                    }
-                    docker_api::conn::TtyChunk::StdOut(inner) => {
-                        ("o", String::from_utf8_lossy(&inner).into_owned())
-                    }
-                    docker_api::conn::TtyChunk::StdErr(inner) => {
-                        ("e", String::from_utf8_lossy(&inner).into_owned())
-                    }
-                };
-                println!("{} {}: {}", &colored_name, &descriptor, &line.trim())
-            }
-            Err(_) => {
-                break;
-            }
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            ExitCode::from(err.exit_code())
         }
     }
+}
 
-    // Container stopped or died, remove from watched list
-    if follow {
-        println!(
-            ">>> {} Container {} stopped",
-            "✗".bright_red(),
-            name.bright_cyan()
-        );
+/// The lines to read back per container: `-l` when given, otherwise every
+/// line of a `--since`/`--until` window or the last `default` ones.
+fn tail(given: Option<usize>, time: &TimeArgs, default: usize) -> Option<usize> {
+    match given {
+        Some(n) => Some(n),
+        None if time.since.is_some() || time.until.is_some() => None,
+        None => Some(default),
     }
-    watched_containers.lock().await.remove(&container_id);
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Args::parse();
-
-    match cli.command {
-        Some(Command::Tui { last_n_lines }) => {
-            tui::run_tui(&cli.url, &cli.container_regex, last_n_lines).await?;
+async fn run(cli: &Args) -> error::Result<()> {
+    let selector = Selector::new(&cli.container_regex, cli.all, &cli.selection)?;
+    // Periodically catch containers whose start event was missed
+    let resync_interval =
+        (cli.resync_interval > 0).then(|| Duration::from_secs(cli.resync_interval));
+
+    match &cli.command {
+        Some(Command::Tui { last_n_lines, time }) => {
+            let mut hosts = Vec::new();
+            for endpoint in Endpoint::resolve_all(&cli.connection)? {
+                hosts.push(get_docker(endpoint).await?);
+            }
+            let options = SourceOptions {
+                follow: true,
+                last_n_lines: tail(*last_n_lines, time, 100),
+                stdout: true,
+                stderr: true,
+                time: time.clone(),
+                resync_interval,
+            };
+            tui::run_tui(hosts, &selector, options).await
         }
+        // Default behavior: logs mode
         None => {
-            // Default behavior: logs mode
-            run_logs_mode(
-                &cli.url,
-                &cli.container_regex,
-                cli.follow,
-                cli.last_n_lines,
-                cli.no_stdout,
-                cli.no_stderr,
-            )
-            .await?;
+            let options = SourceOptions {
+                follow: cli.follow,
+                last_n_lines: tail(cli.last_n_lines, &cli.time, 20),
+                stdout: !cli.no_stdout,
+                stderr: !cli.no_stderr,
+                time: cli.time.clone(),
+                resync_interval,
+            };
+            run_logs_mode(cli, selector, options).await
         }
     }
-
-    Ok(())
 }
 
 async fn run_logs_mode(
-    url: &str,
-    container_regex_str: &str,
-    follow: bool,
-    last_n_lines: usize,
-    no_stdout: bool,
-    no_stderr: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let docker = get_docker(url).await;
-    let container_regex = regex::Regex::new(container_regex_str)?;
-
-    // Shared state for tracking watched containers
-    let watched_containers: Arc<Mutex<HashSet<String>>> = Arc::new(Mutex::new(HashSet::new()));
-    let color_counter = Arc::new(Mutex::new(0usize));
-
-    let log_opts = docker_api::opts::LogsOpts::builder()
-        .follow(follow)
-        .n_lines(last_n_lines)
-        .stdout(!no_stdout)
-        .stderr(!no_stderr)
-        .timestamps(false)
-        .build();
-
-    // Start logging existing containers
-    let containers = docker.containers().list(&Default::default()).await?;
-
-    let mut tasks = Vec::new();
-
-    for container_info in containers {
-        let container_id = match &container_info.id {
-            Some(id) => id.clone(),
-            None => continue,
-        };
-
-        // Check if already watching
-        let mut watched = watched_containers.lock().await;
-        if watched.contains(&container_id) {
-            continue;
-        }
-        watched.insert(container_id.clone());
-        drop(watched);
-
-        let docker_url = url.to_string();
-        let regex = container_regex.clone();
-        let opts = log_opts.clone();
-        let watched = watched_containers.clone();
-        let counter = color_counter.clone();
-
-        let is_follow = follow;
-        let task = tokio::spawn(async move {
-            let mut color_idx = counter.lock().await;
-            let idx = *color_idx;
-            *color_idx += 1;
-            drop(color_idx);
-
-            start_logging_container(
-                docker_url,
-                container_id,
-                regex,
-                opts,
-                idx,
-                watched,
-                is_follow,
-            )
-            .await;
-        });
-        tasks.push(task);
-    }
+    cli: &Args,
+    selector: Selector,
+    options: SourceOptions,
+) -> error::Result<()> {
+    let follow = cli.follow;
+    let printer = Printer {
+        line_filter: LineFilter::new(&cli.grep, &cli.exclude_grep)?.with_context(
+            cli.before_context.or(cli.context_lines).unwrap_or(0),
+            cli.after_context.or(cli.context_lines).unwrap_or(0),
+        ),
+        time: cli.time.clone(),
+        output: cli.output,
+        format: cli.format.clone(),
+        sanitize: cli.sanitize,
+    };
 
-    // If not following, wait for all tasks to complete and exit
-    if !follow {
-        for task in tasks {
-            let _ = task.await;
+    // One source per daemon, all feeding the same subscription
+    let endpoints = Endpoint::resolve_all(&cli.connection)?;
+    let single_host = endpoints.len() == 1;
+    let (sink, mut subscription) = source::channel();
+    let mut errors = Vec::new();
+    for endpoint in endpoints {
+        let (docker, endpoint) = get_docker(endpoint).await?;
+        let source = LogSource::new(docker, &endpoint, selector.clone(), options.clone());
+        match source.start(&sink).await {
+            Ok(()) => {}
+            // A single daemon that cannot be reached is fatal, one of several is not
+            Err(err) if single_host => return Err(err),
+            // The source keeps retrying it
+            Err(err) if follow => eprintln!("{}", err.report()),
+            Err(err) => errors.push(err),
         }
-        return Ok(());
     }
-
-    // If following, monitor Docker events for new containers
-    let event_docker = get_docker(url).await;
-    let event_opts = docker_api::opts::EventsOpts::builder().build();
-
-    let mut events = event_docker.events(&event_opts);
-
-    while let Some(event_result) = events.next().await {
-        match event_result {
-            Ok(event) => {
-                // Check if it's a container start event
-                if event.type_.as_deref() == Some("container")
-                    && event.action.as_deref() == Some("start")
-                {
-                    let container_id = match event.actor.and_then(|a| a.id) {
-                        Some(id) => id,
-                        None => continue,
-                    };
-
-                    // Check if already watching
-                    let mut watched = watched_containers.lock().await;
-                    if watched.contains(&container_id) {
-                        continue;
+    // Without --follow the subscription ends once every container is done
+    drop(sink);
+
+    let mut containers: HashMap<String, ContainerOutput> = HashMap::new();
+    let mut color_counter = 0;
+    let mut sorted = Vec::new();
+    while let Some(event) = subscription.next().await {
+        match event {
+            SourceEvent::Started(meta) => {
+                println!("{}", printer.started(&meta));
+                containers.insert(
+                    meta.id.clone(),
+                    ContainerOutput {
+                        color_index: color_counter,
+                        context: printer.line_filter.context_buffer(),
+                        collected: cli.sort.then(Vec::new),
+                    },
+                );
+                color_counter += 1;
+            }
+            SourceEvent::Log(event) => {
+                if let Some(container) = containers.get_mut(&event.container.id) {
+                    let lines =
+                        printer.log_lines(&mut container.context, container.color_index, event);
+                    match &mut container.collected {
+                        Some(collected) => collected.extend(lines),
+                        None => lines.into_iter().for_each(|(_, text)| println!("{}", text)),
                     }
-                    watched.insert(container_id.clone());
-                    drop(watched);
-
-                    let docker_url = url.to_string();
-                    let regex = container_regex.clone();
-                    let opts = log_opts.clone();
-                    let watched = watched_containers.clone();
-                    let counter = color_counter.clone();
-
-                    tokio::spawn(async move {
-                        let mut color_idx = counter.lock().await;
-                        let idx = *color_idx;
-                        *color_idx += 1;
-                        drop(color_idx);
-
-                        start_logging_container(
-                            docker_url,
-                            container_id,
-                            regex,
-                            opts,
-                            idx,
-                            watched,
-                            true, // Always true in event loop (follow mode)
-                        )
-                        .await;
-                    });
                 }
             }
-            Err(_) => {
-                // Silently ignore event errors
-                continue;
+            SourceEvent::Stopped {
+                container, error, ..
+            } => {
+                if let Some(collected) = containers
+                    .remove(&container.id)
+                    .and_then(|output| output.collected)
+                {
+                    sorted.push(collected);
+                }
+                match error {
+                    // Nobody collects the outcome of a followed container, report it here
+                    Some(err) if follow => eprintln!("{}", err.report()),
+                    Some(err) => errors.push(err),
+                    None => {}
+                }
+                if follow {
+                    println!("{}", printer.stopped(&container));
+                }
+            }
+            SourceEvent::Removed(_) => {}
+            SourceEvent::Disconnected { host } => {
+                println!("{}", printer.daemon_notice(host.as_deref(), false))
+            }
+            SourceEvent::Reconnected { host } => {
+                println!("{}", printer.daemon_notice(host.as_deref(), true))
             }
         }
     }
 
-    Ok(())
+    for (_, line) in merge::merge_sorted_by_key(sorted, |(ts, _)| *ts) {
+        println!("{}", line);
+    }
+    // Report every failure, the last one decides the exit code
+    let last = errors.pop();
+    for err in errors {
+        eprintln!("{}", err.report());
+    }
+    last.map_or(Ok(()), Err)
 }
@@ -0,0 +1,35 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// K-way merges several already sorted sources into one sorted `Vec`.
+///
+/// Entries with equal keys keep the order of their sources, so the merge is
+/// stable both within and across sources.
+pub fn merge_sorted_by_key<T, K, F>(sources: Vec<Vec<T>>, key: F) -> Vec<T>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let total = sources.iter().map(Vec::len).sum();
+    let mut iters: Vec<_> = sources.into_iter().map(Vec::into_iter).collect();
+    let mut heads: Vec<Option<T>> = iters.iter_mut().map(Iterator::next).collect();
+
+    let mut heap = BinaryHeap::with_capacity(heads.len());
+    for (source, head) in heads.iter().enumerate() {
+        if let Some(item) = head {
+            heap.push(Reverse((key(item), source)));
+        }
+    }
+
+    let mut merged = Vec::with_capacity(total);
+    while let Some(Reverse((_, source))) = heap.pop() {
+        if let Some(item) = heads[source].take() {
+            merged.push(item);
+        }
+        heads[source] = iters[source].next();
+        if let Some(item) = &heads[source] {
+            heap.push(Reverse((key(item), source)));
+        }
+    }
+    merged
+}
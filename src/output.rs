@@ -0,0 +1,264 @@
+use crate::ansi;
+use crate::containers::ContainerMeta;
+use crate::grep::{ContextBuffer, ContextLine, LineFilter};
+use crate::lines::LogLine;
+use crate::source::LogEvent;
+use crate::template::{Field, Template};
+use crate::timestamps::TimeArgs;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::Serialize;
+use std::borrow::Cow;
+
+/// How log lines are written in logs mode.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Coloured `name descriptor: line` text
+    Text,
+    /// One JSON object per line (NDJSON)
+    Json,
+}
+
+/// A single NDJSON record.
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonEvent<'a> {
+    /// The container started being watched
+    Started {
+        #[serde(flatten)]
+        container: &'a ContainerMeta,
+    },
+    /// The container's log stream ended
+    Stopped {
+        #[serde(flatten)]
+        container: &'a ContainerMeta,
+    },
+    /// The connection to the Docker daemon was lost
+    Disconnected {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        host: Option<&'a str>,
+    },
+    /// The connection to the Docker daemon was restored
+    Reconnected {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        host: Option<&'a str>,
+    },
+    Log {
+        #[serde(flatten)]
+        container: &'a ContainerMeta,
+        stream: &'static str,
+        timestamp: Option<DateTime<Utc>>,
+        message: Cow<'a, str>,
+        /// Set for lines printed only as grep context
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        context: bool,
+    },
+}
+
+impl<'a> JsonEvent<'a> {
+    pub fn log(container: &'a ContainerMeta, line: &'a LogLine, context: bool) -> Self {
+        JsonEvent::Log {
+            container,
+            stream: line.stream.name(),
+            timestamp: line.timestamp,
+            message: line.message(),
+            context,
+        }
+    }
+
+    pub fn to_line(&self) -> String {
+        // Serializing these plain structs cannot fail
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A rendered output line keyed by its Docker timestamp, used by `--sort`.
+pub type TimedLine = (Option<DateTime<Utc>>, String);
+
+/// Renders the events of logs mode as coloured text or NDJSON.
+#[derive(Debug, Clone)]
+pub struct Printer {
+    pub line_filter: LineFilter,
+    pub time: TimeArgs,
+    pub output: OutputFormat,
+    /// Line template, only used for text output
+    pub format: Option<Template>,
+    /// Drop escape sequences and control characters from messages
+    pub sanitize: bool,
+}
+
+impl Printer {
+    pub fn started(&self, meta: &ContainerMeta) -> String {
+        match self.output {
+            OutputFormat::Json => JsonEvent::Started { container: meta }.to_line(),
+            OutputFormat::Text => format!(
+                ">>> {} Started watching container {}",
+                "✓".bright_green(),
+                meta.display_name().bright_cyan()
+            ),
+        }
+    }
+
+    pub fn stopped(&self, meta: &ContainerMeta) -> String {
+        match self.output {
+            OutputFormat::Json => JsonEvent::Stopped { container: meta }.to_line(),
+            OutputFormat::Text => format!(
+                ">>> {} Container {} stopped",
+                "✗".bright_red(),
+                meta.display_name().bright_cyan()
+            ),
+        }
+    }
+
+    pub fn daemon_notice(&self, host: Option<&str>, connected: bool) -> String {
+        let daemon = match host {
+            Some(host) => format!("Docker daemon {}", host.bright_cyan()),
+            None => "Docker daemon".to_string(),
+        };
+        match (self.output, connected) {
+            (OutputFormat::Json, true) => JsonEvent::Reconnected { host }.to_line(),
+            (OutputFormat::Json, false) => JsonEvent::Disconnected { host }.to_line(),
+            (OutputFormat::Text, true) => {
+                format!(">>> {} {} reconnected", "✓".bright_green(), daemon)
+            }
+            (OutputFormat::Text, false) => format!(
+                ">>> {} {} disconnected, reconnecting...",
+                "✗".bright_red(),
+                daemon
+            ),
+        }
+    }
+
+    /// Renders a log line through the container's grep context buffer,
+    /// returning whatever it lets through: nothing, the line, or the line
+    /// along with its context.
+    pub fn log_lines(
+        &self,
+        context: &mut ContextBuffer<LogLine>,
+        color_index: usize,
+        event: LogEvent,
+    ) -> Vec<TimedLine> {
+        let meta = event.container.clone();
+        let mut line = event.into_line();
+        if self.sanitize {
+            if let Cow::Owned(message) = ansi::sanitize(&line.message()) {
+                line.bytes = message.into_bytes();
+            }
+        }
+        let matched = self.line_filter.matches(&line.message());
+        let outputs = context.push(line, matched);
+        // Separators have no timestamp of their own, they belong to the group that follows
+        let group_timestamp = outputs.iter().find_map(|output| match output {
+            ContextLine::Match(line) | ContextLine::Context(line) => line.timestamp,
+            ContextLine::Separator => None,
+        });
+        outputs
+            .into_iter()
+            .filter_map(|output| {
+                let timestamp = match &output {
+                    ContextLine::Match(line) | ContextLine::Context(line) => line.timestamp,
+                    ContextLine::Separator => group_timestamp,
+                };
+                let text = self.render_line(&meta, color_index, output)?;
+                Some((timestamp, text))
+            })
+            .collect()
+    }
+
+    fn render_line(
+        &self,
+        meta: &ContainerMeta,
+        color_index: usize,
+        output: ContextLine<LogLine>,
+    ) -> Option<String> {
+        let json = self.output == OutputFormat::Json;
+        let colored_name = || colorize_name(&meta.display_name(), color_index);
+        let timestamp_prefix = |line: &LogLine| match &line.timestamp {
+            Some(ts) if self.time.timestamps => format!("{} ", self.time.format(ts).dimmed()),
+            _ => String::new(),
+        };
+
+        let text = match output {
+            ContextLine::Match(line) if json => JsonEvent::log(meta, &line, false).to_line(),
+            ContextLine::Context(line) if json => JsonEvent::log(meta, &line, true).to_line(),
+            // Groups are already delimited by the context flag in JSON
+            ContextLine::Separator if json => return None,
+            ContextLine::Match(line) if self.format.is_some() => {
+                self.render_template(meta, color_index, &line, false)
+            }
+            ContextLine::Context(line) if self.format.is_some() => {
+                self.render_template(meta, color_index, &line, true)
+            }
+            // The template may not start with the name, so the group ends on a line of its own
+            ContextLine::Separator if self.format.is_some() => "--".cyan().to_string(),
+            ContextLine::Match(line) => format!(
+                "{} {}: {}{}",
+                colored_name(),
+                line.stream.descriptor(),
+                timestamp_prefix(&line),
+                self.line_filter.highlight(&line.message())
+            ),
+            ContextLine::Context(line) => format!(
+                "{} {}- {}{}",
+                colored_name(),
+                line.stream.descriptor(),
+                timestamp_prefix(&line),
+                line.message()
+            ),
+            ContextLine::Separator => format!("{} {}", colored_name(), "--".cyan()),
+        };
+        Some(text)
+    }
+
+    /// Renders a log line through the user's `--format` template, `context`
+    /// telling grep context lines from matches.
+    fn render_template(
+        &self,
+        meta: &ContainerMeta,
+        color_index: usize,
+        line: &LogLine,
+        context: bool,
+    ) -> String {
+        let template = match &self.format {
+            Some(template) => template,
+            None => return line.message().into_owned(),
+        };
+        template.render(
+            |field| match field {
+                Field::Host => meta.host.clone().unwrap_or_default(),
+                Field::Name => meta.name.clone(),
+                Field::Id(_) => meta.id.clone(),
+                Field::Image => meta.image.clone().unwrap_or_default(),
+                Field::Stream => line.stream.name().to_string(),
+                Field::Ts => line
+                    .timestamp
+                    .map(|ts| self.time.format(&ts))
+                    .unwrap_or_default(),
+                Field::Label(key) => meta.labels.get(key).cloned().unwrap_or_default(),
+                Field::Msg => line.message().into_owned(),
+                Field::Sep => if context { "-" } else { ":" }.to_string(),
+            },
+            |field, text| match field {
+                Field::Name => colorize_name(&text, color_index).to_string(),
+                Field::Ts => text.dimmed().to_string(),
+                Field::Msg if !context => self.line_filter.highlight(&text),
+                _ => text,
+            },
+        )
+    }
+}
+
+fn colorize_name(name: &str, color_index: usize) -> ColoredString {
+    match color_index % 9 {
+        0 => name.bright_green().clone(),
+        1 => name.bright_blue(),
+        2 => name.bright_yellow(),
+        3 => name.bright_magenta(),
+        4 => name.bright_cyan(),
+        5 => name.bright_white(),
+        6 => name.bright_red(),
+        7 => name.yellow(),
+        8 => name.green(),
+        _ => name.on_black().white(),
+    }
+}
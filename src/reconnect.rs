@@ -0,0 +1,208 @@
+use crate::backend::Backend;
+use crate::error::{self, Error};
+use crate::lines::{LineAssembler, LogLine};
+use chrono::{DateTime, Utc};
+use docker_api::conn::TtyChunk;
+use docker_api::opts::LogsOpts;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Exponential backoff between reconnection attempts.
+#[derive(Debug)]
+pub struct Backoff {
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self {
+            current: INITIAL_BACKOFF,
+        }
+    }
+
+    /// Returns the delay to wait now and doubles the next one.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(MAX_BACKOFF);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = INITIAL_BACKOFF;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Waits until the daemon answers `/_ping` again.
+pub async fn wait_for_daemon(backend: &dyn Backend) {
+    let mut backoff = Backoff::new();
+    loop {
+        tokio::time::sleep(backoff.next_delay()).await;
+        match backend.ping().await {
+            Ok(_) => return,
+            Err(err) => error::warn("daemon still unreachable", &err),
+        }
+    }
+}
+
+/// A container log stream that survives daemon restarts and dropped
+/// connections when following.
+///
+/// Lines must carry Docker timestamps: after a reconnect the stream is
+/// reopened from the last seen timestamp and lines already emitted are
+/// skipped, so nothing is printed twice.
+pub struct ResumableLogs<'a, F> {
+    backend: &'a dyn Backend,
+    container_id: &'a str,
+    /// Builds the logs options, given the timestamp to resume from
+    opts: F,
+    follow: bool,
+    stream: Option<BoxStream<'a, docker_api::Result<TtyChunk>>>,
+    assembler: LineAssembler,
+    pending: VecDeque<LogLine>,
+    last_timestamp: Option<DateTime<Utc>>,
+    resumed: bool,
+    backoff: Backoff,
+    finished: bool,
+    /// Why the stream ended early, if it did
+    error: Option<Error>,
+}
+
+impl<'a, F> ResumableLogs<'a, F>
+where
+    F: Fn(Option<DateTime<Utc>>) -> LogsOpts,
+{
+    pub fn new(backend: &'a dyn Backend, container_id: &'a str, follow: bool, opts: F) -> Self {
+        Self {
+            backend,
+            container_id,
+            opts,
+            follow,
+            stream: None,
+            assembler: LineAssembler::new(true),
+            pending: VecDeque::new(),
+            last_timestamp: None,
+            resumed: false,
+            backoff: Backoff::new(),
+            finished: false,
+            error: None,
+        }
+    }
+
+    /// Returns the next complete line, or `None` once the container's logs
+    /// have ended for good.
+    pub async fn next(&mut self) -> Option<LogLine> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Some(line);
+            }
+            if self.finished {
+                return None;
+            }
+
+            let (backend, container_id) = (self.backend, self.container_id);
+            let stream = match &mut self.stream {
+                Some(stream) => stream,
+                None => {
+                    let opts = (self.opts)(self.last_timestamp);
+                    self.stream.insert(backend.logs(container_id, &opts))
+                }
+            };
+
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    self.backoff.reset();
+                    let lines = self.assembler.push(chunk);
+                    self.enqueue(lines);
+                }
+                Some(Err(err)) => {
+                    self.stream = None;
+                    let err = Error::from_logs(err, self.container_id);
+                    let fatal = matches!(err, Error::UnsupportedLogDriver { .. });
+                    if fatal || !self.follow {
+                        let lines = self.assembler.finish();
+                        self.enqueue(lines);
+                        self.finished = true;
+                        self.error = Some(err);
+                    } else {
+                        error::warn("log stream interrupted", &err);
+                        self.resume_or_finish().await;
+                    }
+                }
+                None if self.follow => {
+                    self.stream = None;
+                    self.resume_or_finish().await;
+                }
+                _ => {
+                    self.stream = None;
+                    let lines = self.assembler.finish();
+                    self.enqueue(lines);
+                    self.finished = true;
+                }
+            }
+        }
+    }
+
+    /// Takes the error that ended the stream, once `next` returned `None`.
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
+    async fn resume_or_finish(&mut self) {
+        if !self.wait_until_resumable().await {
+            let lines = self.assembler.finish();
+            self.enqueue(lines);
+            self.finished = true;
+        }
+    }
+
+    /// Decides whether a dropped stream should be reopened, waiting for the
+    /// daemon to come back if needed. Returns false once the container has
+    /// stopped or is gone.
+    async fn wait_until_resumable(&mut self) -> bool {
+        loop {
+            tokio::time::sleep(self.backoff.next_delay()).await;
+            match self.backend.inspect(self.container_id).await {
+                Ok(info) => {
+                    let running = info.state.and_then(|s| s.running).unwrap_or(false);
+                    if running {
+                        // Partial lines will be sent again from the resume point
+                        self.assembler = LineAssembler::new(true);
+                        self.resumed = true;
+                    }
+                    return running;
+                }
+                // The daemon answered, the container no longer exists
+                Err(docker_api::Error::Fault { .. }) => return false,
+                // Daemon unreachable, keep waiting
+                Err(err) => error::warn("daemon still unreachable", &err),
+            }
+        }
+    }
+
+    fn enqueue(&mut self, lines: Vec<LogLine>) {
+        for line in lines {
+            if self.resumed {
+                // Docker's `since` has second granularity, skip what was already emitted
+                match (line.timestamp, self.last_timestamp) {
+                    (Some(ts), Some(last)) if ts <= last => continue,
+                    _ => self.resumed = false,
+                }
+            }
+            if line.timestamp.is_some() {
+                self.last_timestamp = line.timestamp;
+            }
+            self.pending.push_back(line);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use chrono::{DateTime, Utc};
+use docker_api::opts::{
+    ContainerFilter, ContainerListOpts, ContainerStatus, EventFilter, EventFilterType, EventsOpts,
+};
+use regex::Regex;
+use std::collections::HashMap;
+
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+
+/// Container state accepted by `--status`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusArg {
+    Created,
+    Restarting,
+    Running,
+    Removing,
+    Paused,
+    Exited,
+    Dead,
+}
+
+impl StatusArg {
+    fn to_docker(self) -> ContainerStatus {
+        match self {
+            StatusArg::Created => ContainerStatus::Created,
+            StatusArg::Restarting => ContainerStatus::Restarting,
+            StatusArg::Running => ContainerStatus::Running,
+            StatusArg::Removing => ContainerStatus::Removing,
+            StatusArg::Paused => ContainerStatus::Paused,
+            StatusArg::Exited => ContainerStatus::Exited,
+            StatusArg::Dead => ContainerStatus::Dead,
+        }
+    }
+}
+
+// Container selection options shared by the logs mode and the TUI.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct SelectionArgs {
+    /// Only containers with this label, as key or key=value (repeatable)
+    #[clap(long, value_parser, global = true)]
+    pub label: Vec<String>,
+    /// Only containers whose image matches this regex
+    #[clap(long, value_parser, global = true)]
+    pub image: Option<String>,
+    /// Only containers of this compose project
+    #[clap(long, value_parser, global = true)]
+    pub project: Option<String>,
+    /// Only containers of this compose service
+    #[clap(long, value_parser, global = true)]
+    pub service: Option<String>,
+    /// Only containers in this state (repeatable)
+    #[clap(long, value_enum, global = true)]
+    pub status: Vec<StatusArg>,
+    /// Skip containers whose name matches this regex
+    #[clap(long, value_parser, global = true)]
+    pub exclude_regex: Option<String>,
+}
+
+/// Decides which containers get watched.
+///
+/// Label, compose and status filters are pushed down to Docker's list and
+/// events filters, and so is a plain container name; other name regexes and
+/// image regexes can only be checked client-side.
+#[derive(Debug, Clone)]
+pub struct Selector {
+    name: Regex,
+    exclude_name: Option<Regex>,
+    image: Option<Regex>,
+    labels: Vec<(String, Option<String>)>,
+    statuses: Vec<StatusArg>,
+    all: bool,
+}
+
+impl Selector {
+    pub fn new(
+        container_regex: &str,
+        all: bool,
+        args: &SelectionArgs,
+    ) -> Result<Self, regex::Error> {
+        let mut labels: Vec<(String, Option<String>)> = args
+            .label
+            .iter()
+            .map(|label| match label.split_once('=') {
+                Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                None => (label.clone(), None),
+            })
+            .collect();
+        if let Some(project) = &args.project {
+            labels.push((COMPOSE_PROJECT_LABEL.to_string(), Some(project.clone())));
+        }
+        if let Some(service) = &args.service {
+            labels.push((COMPOSE_SERVICE_LABEL.to_string(), Some(service.clone())));
+        }
+
+        Ok(Self {
+            name: Regex::new(container_regex)?,
+            exclude_name: args.exclude_regex.as_deref().map(Regex::new).transpose()?,
+            image: args.image.as_deref().map(Regex::new).transpose()?,
+            labels,
+            statuses: args.status.clone(),
+            // Any state other than running is only listed with `all`
+            all: all || !args.status.is_empty(),
+        })
+    }
+
+    /// Whether stopped containers are listed too.
+    pub fn all(&self) -> bool {
+        self.all
+    }
+
+    /// Options for the initial container listing.
+    pub fn list_opts(&self) -> ContainerListOpts {
+        let mut filters: Vec<ContainerFilter> = self
+            .labels
+            .iter()
+            .map(|(key, value)| match value {
+                Some(value) => ContainerFilter::Label(key.clone(), value.clone()),
+                None => ContainerFilter::LabelKey(key.clone()),
+            })
+            .collect();
+        filters.extend(
+            self.statuses
+                .iter()
+                .map(|status| ContainerFilter::Status(status.to_docker())),
+        );
+        if let Some(name) = self.docker_name_filter() {
+            filters.push(ContainerFilter::Name(name.to_string()));
+        }
+
+        ContainerListOpts::builder()
+            .all(self.all)
+            .filter(filters)
+            .build()
+    }
+
+    /// The container regex as Docker's `name` filter, when Go's regex engine
+    /// reads it the same way. Only a run of name characters, optionally
+    /// anchored, qualifies; anything else, the default match-all included,
+    /// is left to the client-side check.
+    fn docker_name_filter(&self) -> Option<&str> {
+        let pattern = self.name.as_str();
+        let body = pattern.strip_prefix('^').unwrap_or(pattern);
+        let body = body.strip_suffix('$').unwrap_or(body);
+        let plain = !body.is_empty()
+            && body
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        plain.then_some(pattern)
+    }
+
+    /// Options for the Docker events stream, limited to container starts.
+    /// `since` replays events missed while disconnected.
+    pub fn events_opts(&self, since: Option<DateTime<Utc>>) -> EventsOpts {
+        let mut filters = vec![
+            EventFilter::Type(EventFilterType::Container),
+            EventFilter::Event("start".to_string()),
+        ];
+        filters.extend(self.labels.iter().map(|(key, value)| match value {
+            Some(value) => EventFilter::Label(format!("{}={}", key, value)),
+            None => EventFilter::Label(key.clone()),
+        }));
+
+        let builder = EventsOpts::builder().filter(filters);
+        match since {
+            Some(since) => builder.since(&since).build(),
+            None => builder.build(),
+        }
+    }
+
+    /// Whether containers that start while following should be watched.
+    pub fn watches_started(&self) -> bool {
+        self.statuses.is_empty() || self.statuses.contains(&StatusArg::Running)
+    }
+
+    /// Client-side check of an inspected container.
+    pub fn matches(
+        &self,
+        name: &str,
+        image: Option<&str>,
+        labels: &HashMap<String, String>,
+    ) -> bool {
+        if !self.name.is_match(name) {
+            return false;
+        }
+        if self.exclude_name.as_ref().is_some_and(|r| r.is_match(name)) {
+            return false;
+        }
+        if let Some(image_regex) = &self.image {
+            if !image.is_some_and(|image| image_regex.is_match(image)) {
+                return false;
+            }
+        }
+        self.labels.iter().all(|(key, value)| match value {
+            Some(value) => labels.get(key) == Some(value),
+            None => labels.contains_key(key),
+        })
+    }
+}
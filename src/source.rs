@@ -0,0 +1,503 @@
+use crate::backend::Backend;
+use crate::containers::{ContainerCache, ContainerMeta};
+use crate::endpoint::Endpoint;
+use crate::error::{self, Error, Result};
+use crate::lines::{LogLine, Stream};
+use crate::reconnect::{self, ResumableLogs};
+use crate::selection::Selector;
+use crate::timestamps::TimeArgs;
+use chrono::{DateTime, TimeZone, Utc};
+use docker_api::opts::LogsOpts;
+use futures::StreamExt;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::AbortHandle;
+
+/// A log line of a watched container.
+#[derive(Debug, Clone)]
+pub struct LogEvent {
+    pub container: Arc<ContainerMeta>,
+    pub stream: Stream,
+    /// Docker timestamp of the line
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The line as the container wrote it, without its newline
+    pub bytes: Vec<u8>,
+}
+
+impl LogEvent {
+    /// The line as text, invalid UTF-8 replaced by U+FFFD.
+    pub fn message(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.bytes)
+    }
+
+    fn new(container: Arc<ContainerMeta>, line: LogLine) -> Self {
+        Self {
+            container,
+            stream: line.stream,
+            timestamp: line.timestamp,
+            bytes: line.bytes,
+        }
+    }
+
+    /// The line alone, without the container it came from.
+    pub fn into_line(self) -> LogLine {
+        LogLine {
+            stream: self.stream,
+            timestamp: self.timestamp,
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// Everything a [`Subscription`] reports, in the order it happened for a
+/// given container.
+#[derive(Debug)]
+pub enum SourceEvent {
+    /// A container started being watched
+    Started(Arc<ContainerMeta>),
+    Log(LogEvent),
+    /// A container's log stream ended
+    Stopped {
+        container: Arc<ContainerMeta>,
+        /// Exit code of the container, only looked up when following with
+        /// stopped containers selected
+        exit_code: Option<isize>,
+        /// Why the stream ended early, if it did
+        error: Option<Error>,
+    },
+    /// A previously reported container is no longer listed by the daemon
+    Removed(Arc<ContainerMeta>),
+    /// The connection to the daemon was lost
+    Disconnected {
+        host: Option<String>,
+    },
+    /// The connection to the daemon was restored
+    Reconnected {
+        host: Option<String>,
+    },
+}
+
+/// What a [`LogSource`] reads and for how long.
+#[derive(Debug, Clone)]
+pub struct SourceOptions {
+    /// Keep streaming and watch containers started later
+    pub follow: bool,
+    /// Lines read back per container, `None` for all of them
+    pub last_n_lines: Option<usize>,
+    pub stdout: bool,
+    pub stderr: bool,
+    pub time: TimeArgs,
+    /// Time between container list resyncs while following
+    pub resync_interval: Option<Duration>,
+}
+
+impl SourceOptions {
+    /// Builds the logs options, resuming after `resume_from` when reconnecting.
+    fn logs_opts(&self, resume_from: Option<DateTime<Utc>>) -> LogsOpts {
+        let builder = LogsOpts::builder()
+            .follow(self.follow)
+            .stdout(self.stdout)
+            .stderr(self.stderr);
+        let builder = self.time.apply(match self.last_n_lines {
+            Some(n) => builder.n_lines(n),
+            None => builder.all(),
+        });
+        let builder = match resume_from {
+            Some(ts) => builder.all().since(&ts),
+            None => builder,
+        };
+        // Timestamps are always needed to resume and order lines, even when not displayed
+        builder.timestamps(true).build()
+    }
+
+    /// Whether dropped log streams are reopened. A stream bounded by
+    /// `--until` ends on its own and must not be resumed.
+    fn resumable(&self) -> bool {
+        self.follow && self.time.until.is_none()
+    }
+}
+
+/// Creates a subscription along with the sink that [`LogSource::start`]
+/// feeds. The subscription ends once the sink and every source fed by it
+/// are done, which only happens when not following.
+pub fn channel() -> (EventSink, Subscription) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (EventSink(sender), Subscription(receiver))
+}
+
+/// The sending side of a [`Subscription`].
+#[derive(Debug, Clone)]
+pub struct EventSink(mpsc::UnboundedSender<SourceEvent>);
+
+impl EventSink {
+    fn send(&self, event: SourceEvent) {
+        // Nobody is listening any more, the program is shutting down
+        let _ = self.0.send(event);
+    }
+}
+
+/// The events of one or more [`LogSource`]s, merged as they happen.
+#[derive(Debug)]
+pub struct Subscription(mpsc::UnboundedReceiver<SourceEvent>);
+
+impl Subscription {
+    pub async fn next(&mut self) -> Option<SourceEvent> {
+        self.0.recv().await
+    }
+}
+
+impl futures::Stream for Subscription {
+    type Item = SourceEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<SourceEvent>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+/// The containers of one daemon selected by a [`Selector`], turned into a
+/// stream of [`SourceEvent`]s.
+///
+/// Starting a source lists the selected containers and reads the logs of
+/// each one. When following, it also watches Docker events for containers
+/// started later, periodically lists containers again to catch missed
+/// events, and survives the daemon going away.
+pub struct LogSource {
+    host: Option<String>,
+    url: String,
+    backend: Arc<dyn Backend>,
+    selector: Selector,
+    options: SourceOptions,
+}
+
+impl LogSource {
+    pub fn new(
+        backend: Arc<dyn Backend>,
+        endpoint: &Endpoint,
+        selector: Selector,
+        options: SourceOptions,
+    ) -> Self {
+        Self {
+            host: endpoint.alias.clone(),
+            url: endpoint.url.clone(),
+            backend,
+            selector,
+            options,
+        }
+    }
+
+    /// Lists the selected containers and starts reading their logs into
+    /// `sink`.
+    ///
+    /// A failed listing is returned so the caller can decide whether it is
+    /// fatal. When following, the source keeps running anyway and picks the
+    /// containers up once the daemon answers.
+    pub async fn start(self, sink: &EventSink) -> Result<()> {
+        let shared = Arc::new(Shared {
+            cache: ContainerCache::new(self.host.clone()),
+            host: self.host,
+            url: self.url,
+            backend: self.backend,
+            selector: self.selector,
+            options: self.options,
+            watched: Mutex::new(HashMap::new()),
+            finished: Mutex::new(HashSet::new()),
+            reported: Mutex::new(HashMap::new()),
+            sink: sink.clone(),
+        });
+
+        let listing = watch_existing(&shared).await;
+        if !shared.options.follow {
+            return listing;
+        }
+
+        // Periodically catch containers whose start event was missed
+        if let Some(interval) = shared.options.resync_interval {
+            let shared = shared.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                // The first tick completes immediately, right after the initial listing
+                ticker.tick().await;
+                loop {
+                    ticker.tick().await;
+                    resync(&shared).await;
+                }
+            });
+        }
+        tokio::spawn(follow_events(shared));
+        listing
+    }
+}
+
+/// State of a started source, shared by its background tasks.
+struct Shared {
+    /// Alias of the daemon, set when tailing several
+    host: Option<String>,
+    url: String,
+    backend: Arc<dyn Backend>,
+    selector: Selector,
+    options: SourceOptions,
+    cache: ContainerCache,
+    /// Containers with a running log task, so it can be stopped on resync
+    watched: Mutex<HashMap<String, AbortHandle>>,
+    /// Containers whose logs ended while they may still be running, bounded
+    /// by `--until` or unreadable. Watching them again would only repeat
+    /// the same lines or error, so they are left alone until they are gone.
+    finished: Mutex<HashSet<String>>,
+    /// Containers announced with `Started`, until they are `Removed`
+    reported: Mutex<HashMap<String, Arc<ContainerMeta>>>,
+    sink: EventSink,
+}
+
+impl Shared {
+    fn selects(&self, meta: &ContainerMeta) -> bool {
+        self.selector
+            .matches(&meta.name, meta.image.as_deref(), &meta.labels)
+    }
+
+    /// Whether stopped containers stay reported until a resync finds them
+    /// removed, which only happens when following with --all.
+    fn lists_stopped(&self) -> bool {
+        self.options.follow && self.selector.all() && self.options.resync_interval.is_some()
+    }
+}
+
+/// Lists the daemon's containers and starts a log task for each selected one.
+async fn watch_existing(shared: &Arc<Shared>) -> Result<()> {
+    let containers = shared
+        .backend
+        .list(&shared.selector.list_opts())
+        .await
+        .map_err(|err| Error::from_docker(err, &shared.url))?;
+
+    for container_info in containers {
+        // The listing already carries name, image and labels, no need to inspect
+        let meta = match ContainerMeta::from_summary(&container_info) {
+            Some(meta) => meta,
+            None => continue,
+        };
+        if shared.selects(&meta) {
+            spawn_watcher(shared, meta).await;
+        }
+    }
+    Ok(())
+}
+
+/// Starts a log task for the container unless one is already running or
+/// its logs were read to the end.
+async fn spawn_watcher(shared: &Arc<Shared>, meta: ContainerMeta) {
+    let container_id = meta.id.clone();
+
+    // Hold the lock while spawning so the task cannot unregister itself first
+    let mut watched = shared.watched.lock().await;
+    if watched.contains_key(&container_id) || shared.finished.lock().await.contains(&container_id) {
+        return;
+    }
+    shared.cache.insert(meta).await;
+    let task = tokio::spawn(watch_container(shared.clone(), container_id.clone()));
+    watched.insert(container_id, task.abort_handle());
+}
+
+async fn watch_container(shared: Arc<Shared>, container_id: String) {
+    let meta = match shared
+        .cache
+        .get(shared.backend.as_ref(), &container_id)
+        .await
+    {
+        Ok(meta) => Arc::new(meta),
+        Err(err) => {
+            // Most likely removed right after starting
+            error::warn(&format!("cannot inspect container {}", container_id), &err);
+            shared.watched.lock().await.remove(&container_id);
+            return;
+        }
+    };
+    shared
+        .reported
+        .lock()
+        .await
+        .insert(container_id.clone(), meta.clone());
+    shared.sink.send(SourceEvent::Started(meta.clone()));
+
+    let mut stream = ResumableLogs::new(
+        shared.backend.as_ref(),
+        &container_id,
+        shared.options.resumable(),
+        |since| shared.options.logs_opts(since),
+    );
+    while let Some(line) = stream.next().await {
+        // Docker widened the window to whole seconds
+        if line
+            .timestamp
+            .is_some_and(|ts| !shared.options.time.contains(&ts))
+        {
+            continue;
+        }
+        shared
+            .sink
+            .send(SourceEvent::Log(LogEvent::new(meta.clone(), line)));
+    }
+    let error = stream.take_error().map(|err| match err {
+        Error::UnsupportedLogDriver { .. } => Error::UnsupportedLogDriver {
+            container: meta.display_name(),
+        },
+        err => err,
+    });
+
+    // A stopped container stays listed with --all, along with how it exited
+    let exit_code = if shared.options.follow && shared.selector.all() {
+        shared
+            .backend
+            .inspect(&container_id)
+            .await
+            .ok()
+            .and_then(|info| info.state)
+            .and_then(|state| state.exit_code)
+    } else {
+        None
+    };
+
+    if shared.options.follow && (!shared.options.resumable() || error.is_some()) {
+        shared.finished.lock().await.insert(container_id.clone());
+    }
+    shared.cache.remove(&container_id).await;
+    if !shared.lists_stopped() {
+        shared.reported.lock().await.remove(&container_id);
+    }
+
+    // A resync may have stopped the task and reported it already
+    let was_watched = shared.watched.lock().await.remove(&container_id).is_some();
+    if was_watched {
+        shared.sink.send(SourceEvent::Stopped {
+            container: meta,
+            exit_code,
+            error,
+        });
+    }
+}
+
+/// Lists containers again, starting log tasks for running containers that
+/// are not watched yet and dropping containers no longer listed.
+async fn resync(shared: &Arc<Shared>) {
+    // Containers reported once the listing is sent may have started after
+    // Docker built it, only the ones reported before can be missing from it
+    let known: HashSet<String> = shared.reported.lock().await.keys().cloned().collect();
+    let containers = match shared.backend.list(&shared.selector.list_opts()).await {
+        Ok(containers) => containers,
+        // The daemon is unreachable, the events loop takes care of reconnecting
+        Err(err) => {
+            error::warn("resync failed to list containers", &err);
+            return;
+        }
+    };
+
+    let mut present = HashSet::new();
+    for container_info in containers {
+        let meta = match ContainerMeta::from_summary(&container_info) {
+            Some(meta) => meta,
+            None => continue,
+        };
+        if !shared.selects(&meta) {
+            continue;
+        }
+        present.insert(meta.id.clone());
+        // With --all, stopped containers are listed too but have nothing to follow
+        let running = container_info.state.as_deref() == Some("running");
+        if running && shared.selector.watches_started() {
+            spawn_watcher(shared, meta).await;
+        }
+    }
+
+    shared
+        .finished
+        .lock()
+        .await
+        .retain(|id| present.contains(id));
+
+    let mut watched = shared.watched.lock().await;
+    let mut reported = shared.reported.lock().await;
+    let gone = known.into_iter().filter(|id| !present.contains(id));
+    for id in gone {
+        let meta = match reported.remove(&id) {
+            Some(meta) => meta,
+            None => continue,
+        };
+        shared.cache.remove(&id).await;
+        if let Some(handle) = watched.remove(&id) {
+            handle.abort();
+            shared.sink.send(SourceEvent::Stopped {
+                container: meta.clone(),
+                exit_code: None,
+                error: None,
+            });
+        }
+        shared.sink.send(SourceEvent::Removed(meta));
+    }
+}
+
+/// Monitors Docker events for new containers, subscribing again from the
+/// last seen event whenever the daemon goes away.
+///
+/// Docker replays events from the whole second of `since`, so replayed
+/// events up to the last one handled are told apart by their nanosecond
+/// time and skipped, lest stopped containers be watched again.
+async fn follow_events(shared: Arc<Shared>) {
+    let mut last_event: Option<DateTime<Utc>> = None;
+    loop {
+        // Only container start events are requested from Docker
+        let event_opts = shared.selector.events_opts(last_event);
+        let mut events = shared.backend.events(&event_opts);
+
+        while let Some(event_result) = events.next().await {
+            let event = match event_result {
+                Ok(event) => event,
+                // The stream is broken, reconnect below
+                Err(err) => {
+                    error::warn("event stream interrupted", &err);
+                    break;
+                }
+            };
+            let time = match event.time_nano {
+                Some(nanos) => Some(Utc.timestamp_nanos(nanos)),
+                None => event.time.and_then(|t| Utc.timestamp_opt(t, 0).single()),
+            };
+            if let Some(time) = time {
+                if last_event.is_some_and(|last| time <= last) {
+                    continue;
+                }
+                last_event = Some(time);
+            }
+            if !shared.selector.watches_started() {
+                continue;
+            }
+            let meta = match event
+                .actor
+                .as_ref()
+                .and_then(ContainerMeta::from_event_actor)
+            {
+                Some(meta) => meta,
+                None => continue,
+            };
+            if shared.selects(&meta) {
+                spawn_watcher(&shared, meta).await;
+            }
+        }
+
+        shared.sink.send(SourceEvent::Disconnected {
+            host: shared.host.clone(),
+        });
+        reconnect::wait_for_daemon(shared.backend.as_ref()).await;
+        shared.sink.send(SourceEvent::Reconnected {
+            host: shared.host.clone(),
+        });
+        // Catch up on containers that were never listed, e.g. when the daemon
+        // was down at startup
+        if last_event.is_none() {
+            resync(&shared).await;
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use unicode_width::UnicodeWidthStr;
+
+/// A placeholder that can appear in a `--format` template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    /// Alias of the daemon, empty with a single daemon
+    Host,
+    Name,
+    /// Container id, optionally truncated to the given length
+    Id(Option<usize>),
+    Image,
+    Stream,
+    Ts,
+    Label(String),
+    Msg,
+    /// `:` on lines matching `--grep`, `-` on context lines around them
+    Sep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field {
+        field: Field,
+        align: Option<(Align, usize)>,
+    },
+}
+
+/// A user-defined line layout such as `{name:<20} {ts} {msg}`.
+///
+/// Placeholders are `{field}`, `{field:arg}` and either of those followed by
+/// an alignment spec `:<N`, `:>N` or `:^N`. `{id:N}` truncates the id and
+/// `{label:KEY}` looks up a container label. `{sep}` tells grep matches
+/// from context lines the way the default layout does. Use `{{` and `}}`
+/// for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => return Err(format!("unclosed placeholder '{{{}'", spec)),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(parse_placeholder(&spec)?);
+                }
+                '}' => return Err("unmatched '}' in format, use '}}' for a literal brace".into()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Renders the template. `value` returns the plain text for a field and
+    /// `style` decorates it after truncation and padding have been applied.
+    pub fn render<V, S>(&self, value: V, style: S) -> String
+    where
+        V: Fn(&Field) -> String,
+        S: Fn(&Field, String) -> String,
+    {
+        let mut result = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => result.push_str(text),
+                Part::Field { field, align } => {
+                    let mut text = value(field);
+                    if let Field::Id(Some(len)) = field {
+                        text = text.chars().take(*len).collect();
+                    }
+                    if let Some((align, width)) = align {
+                        text = pad(&text, *align, *width);
+                    }
+                    result.push_str(&style(field, text));
+                }
+            }
+        }
+        result
+    }
+}
+
+fn parse_placeholder(spec: &str) -> Result<Part, String> {
+    let mut pieces: Vec<&str> = spec.split(':').collect();
+
+    // A trailing piece starting with an alignment character is the padding spec
+    let mut align = None;
+    if pieces.len() > 1 {
+        let last = pieces[pieces.len() - 1];
+        let kind = match last.chars().next() {
+            Some('<') => Some(Align::Left),
+            Some('>') => Some(Align::Right),
+            Some('^') => Some(Align::Center),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let width = last[1..]
+                .parse()
+                .map_err(|_| format!("invalid width in '{{{}}}'", spec))?;
+            align = Some((kind, width));
+            pieces.pop();
+        }
+    }
+
+    let field = match pieces.as_slice() {
+        ["host"] => Field::Host,
+        ["name"] => Field::Name,
+        ["id"] => Field::Id(None),
+        ["id", len] => Field::Id(Some(
+            len.parse()
+                .map_err(|_| format!("invalid id length in '{{{}}}'", spec))?,
+        )),
+        ["image"] => Field::Image,
+        ["stream"] => Field::Stream,
+        ["ts"] => Field::Ts,
+        ["msg"] => Field::Msg,
+        ["sep"] => Field::Sep,
+        ["label", key @ ..] if !key.is_empty() => Field::Label(key.join(":")),
+        _ => return Err(format!("unknown placeholder '{{{}}}'", spec)),
+    };
+
+    Ok(Part::Field { field, align })
+}
+
+fn pad(text: &str, align: Align, width: usize) -> String {
+    let fill = width.saturating_sub(text.width());
+    match align {
+        Align::Left => format!("{}{}", text, " ".repeat(fill)),
+        Align::Right => format!("{}{}", " ".repeat(fill), text),
+        Align::Center => format!(
+            "{}{}{}",
+            " ".repeat(fill / 2),
+            text,
+            " ".repeat(fill - fill / 2)
+        ),
+    }
+}
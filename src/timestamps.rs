@@ -0,0 +1,166 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+// Time related options shared by the logs mode and the TUI.
+#[derive(clap::Args, Debug, Clone)]
+pub struct TimeArgs {
+    /// Show timestamps
+    #[clap(default_value_t = false, short, long, value_parser)]
+    pub timestamps: bool,
+    /// Only show logs since this time (RFC3339, Unix epoch or relative like 15m)
+    #[clap(long, value_parser = parse_time)]
+    pub since: Option<DateTime<Utc>>,
+    /// Only show logs until this time (RFC3339, Unix epoch or relative like 15m)
+    #[clap(long, value_parser = parse_time)]
+    pub until: Option<DateTime<Utc>>,
+    /// Timestamp display format (strftime syntax)
+    #[clap(default_value = DEFAULT_TIMESTAMP_FORMAT, long, value_parser = parse_timestamp_format)]
+    pub timestamp_format: String,
+}
+
+impl Default for TimeArgs {
+    fn default() -> Self {
+        Self {
+            timestamps: false,
+            since: None,
+            until: None,
+            timestamp_format: DEFAULT_TIMESTAMP_FORMAT.to_string(),
+        }
+    }
+}
+
+impl TimeArgs {
+    /// Applies the time window to a logs builder. Docker only takes whole
+    /// seconds, so the window is widened to the seconds it touches rather
+    /// than losing the lines of a partial second; [`contains`](Self::contains)
+    /// trims the lines back to the window.
+    pub fn apply(
+        &self,
+        mut builder: docker_api::opts::LogsOptsBuilder,
+    ) -> docker_api::opts::LogsOptsBuilder {
+        // docker-api truncates, which rounds `since` down already
+        if let Some(since) = &self.since {
+            builder = builder.since(since);
+        }
+        if let Some(until) = &self.until {
+            let until = if until.timestamp_subsec_nanos() > 0 {
+                *until + Duration::seconds(1)
+            } else {
+                *until
+            };
+            builder = builder.until(&until);
+        }
+        builder
+    }
+
+    /// Whether a line written at `timestamp` falls within `--since` and
+    /// `--until`, both included.
+    pub fn contains(&self, timestamp: &DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| *timestamp >= since)
+            && self.until.is_none_or(|until| *timestamp <= until)
+    }
+
+    /// Renders a Docker timestamp using the configured format.
+    pub fn format(&self, timestamp: &DateTime<Utc>) -> String {
+        timestamp.format(&self.timestamp_format).to_string()
+    }
+}
+
+/// Parses a point in time given as RFC3339, a Unix epoch (optionally with a
+/// fractional part) or a duration relative to now such as `15m` or `1h30m`.
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    if let Some(dt) = parse_epoch(s) {
+        return Ok(dt);
+    }
+
+    if let Some(duration) = parse_relative(s) {
+        return Ok(Utc::now() - duration);
+    }
+
+    Err(format!(
+        "invalid time '{}': expected RFC3339, Unix epoch or relative duration like 15m",
+        s
+    ))
+}
+
+fn parse_epoch(s: &str) -> Option<DateTime<Utc>> {
+    let (secs, frac) = match s.split_once('.') {
+        Some((secs, frac)) => (secs, frac),
+        None => (s, ""),
+    };
+    if secs.is_empty() || !frac.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let secs: i64 = secs.parse().ok()?;
+    let nanos = if frac.is_empty() {
+        0
+    } else {
+        // Right-pad to nanosecond precision, dropping anything finer
+        format!("{:0<9}", &frac[..frac.len().min(9)]).parse().ok()?
+    };
+    Utc.timestamp_opt(secs, nanos).single()
+}
+
+fn parse_relative(s: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut seen_unit = false;
+
+    for ch in s.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        let value: i64 = number.parse().ok()?;
+        number.clear();
+        total += match ch {
+            's' => Duration::seconds(value),
+            'm' => Duration::minutes(value),
+            'h' => Duration::hours(value),
+            'd' => Duration::days(value),
+            'w' => Duration::weeks(value),
+            _ => return None,
+        };
+        seen_unit = true;
+    }
+
+    if seen_unit && number.is_empty() {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+fn parse_timestamp_format(s: &str) -> Result<String, String> {
+    if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+        Err(format!("invalid timestamp format '{}'", s))
+    } else {
+        Ok(s.to_string())
+    }
+}
+
+/// Splits the RFC3339 prefix Docker adds when timestamps are requested.
+/// The rest of the line is left as raw bytes, it need not be UTF-8.
+pub fn split_timestamp(line: &[u8]) -> (Option<DateTime<Utc>>, &[u8]) {
+    let (prefix, rest) = match line.iter().position(|&b| b == b' ') {
+        Some(pos) => (&line[..pos], &line[pos + 1..]),
+        // Empty log line, only the timestamp was sent
+        None => (line, &line[line.len()..]),
+    };
+    let parsed = std::str::from_utf8(prefix)
+        .ok()
+        .and_then(|prefix| DateTime::parse_from_rfc3339(prefix).ok());
+    match parsed {
+        Some(dt) => (Some(dt.with_timezone(&Utc)), rest),
+        None => (None, line),
+    }
+}
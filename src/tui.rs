@@ -1,9 +1,17 @@
+use crate::ansi::{self, StyledText};
+use crate::backend::Backend;
+use crate::endpoint::Endpoint;
+use crate::error;
+use crate::merge::merge_sorted_by_key;
+use crate::selection::Selector;
+use crate::source::{self, LogEvent, LogSource, SourceEvent, SourceOptions, Subscription};
+use crate::timestamps::TimeArgs;
+use chrono::{DateTime, Utc};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use futures::StreamExt;
 use ratatui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout},
@@ -12,30 +20,112 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
     Frame, Terminal,
 };
-use std::collections::{HashMap, VecDeque};
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::io;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 #[derive(Debug, Clone)]
 struct ContainerInfo {
     id: String,
+    /// Display name, prefixed with the host alias when tailing several daemons
     name: String,
+    host: Option<String>,
     selected: bool,
     color_index: usize,
+    /// Exit code once the container has stopped (only kept with `--all`)
+    exit_code: Option<isize>,
+}
+
+/// A formatted log line along with the Docker timestamp used to order it.
+#[derive(Debug, Clone)]
+struct LogEntry {
+    timestamp: Option<DateTime<Utc>>,
+    text: String,
+    /// Where the message starts in `text`, after the name, stream and timestamp
+    message_start: usize,
+}
+
+/// How long the "reconnected" notice stays visible.
+const RECONNECTED_NOTICE_DURATION: Duration = Duration::from_secs(5);
+
+/// How long a warning stays in the logs pane title.
+const WARNING_DURATION: Duration = Duration::from_secs(10);
+
+/// Rows scrolled by one notch of the mouse wheel.
+const WHEEL_SCROLL_ROWS: usize = 3;
+
+/// Log lines kept when a whole time window is read rather than a tail.
+const WINDOW_MAX_LOGS: usize = 10_000;
+
+/// Connection state of the Docker daemon shown in the logs pane title.
+#[derive(Debug, Clone, Copy)]
+enum DaemonNotice {
+    Disconnected,
+    Reconnected(Instant),
 }
 
 struct AppState {
     containers: Vec<ContainerInfo>,
     list_state: ListState,
-    logs: VecDeque<String>,
+    logs: VecDeque<LogEntry>,
     max_logs: usize,
-    container_logs: HashMap<String, VecDeque<String>>,
+    container_logs: HashMap<String, VecDeque<LogEntry>>,
     color_counter: usize,
     show_info: bool,
     info_text: String,
     select_all_focused: bool,
+    /// Connection state per daemon alias
+    daemon_notices: BTreeMap<Option<String>, DaemonNotice>,
+    /// Latest error worth showing, with the time it happened
+    warning: Option<(String, Instant)>,
+    /// The displayed logs as they were when pausing, new lines keep going to `logs`
+    paused: Option<VecDeque<LogEntry>>,
+    /// Lines added to the displayed logs since pausing
+    new_lines: usize,
+    /// Rows the logs pane is scrolled back from the bottom
+    scroll_back: usize,
+    /// Rows of the logs pane at the last draw, the distance of a page scroll
+    page_rows: usize,
+    search: Option<Search>,
+    /// Only lines passing it are displayed
+    filter: Option<LogFilter>,
+    /// The `f` prompt while typing, with the filter to restore on Esc
+    filter_prompt: Option<(String, Option<LogFilter>)>,
+    /// Show the colours of container output rather than stripping them
+    ansi_colors: bool,
+}
+
+/// The `f` line filter, an interactive `--grep` or `--exclude-grep`.
+#[derive(Clone)]
+struct LogFilter {
+    /// As typed, a leading `!` keeps the lines that do not match
+    input: String,
+    regex: Regex,
+    invert: bool,
+    /// The pattern is not a valid regex and is matched as plain text
+    plain: bool,
+}
+
+/// An incremental search through the displayed logs.
+#[derive(Default)]
+struct Search {
+    /// Text typed after `/`
+    input: String,
+    /// Whether keys still go to the prompt
+    typing: bool,
+    /// `None` until something is typed
+    regex: Option<Regex>,
+    /// The input is not a valid regex and is matched as plain text
+    plain: bool,
+    /// Displayed line of the current match
+    hit: Option<usize>,
+    /// Scroll the current match into view at the next draw
+    reveal: bool,
 }
 
 fn get_color(index: usize) -> Color {
@@ -53,28 +143,150 @@ fn get_color(index: usize) -> Color {
     }
 }
 
-fn strip_ansi_codes(s: &str) -> String {
-    let mut result = String::new();
-    let mut chars = s.chars().peekable();
-
-    while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
-            // ESC character - start of ANSI escape sequence
-            if chars.peek() == Some(&'[') {
-                chars.next(); // consume '['
-                              // Skip until we find a letter (the command character)
-                while let Some(&next_ch) = chars.peek() {
-                    chars.next();
-                    if next_ch.is_ascii_alphabetic() {
-                        break;
-                    }
-                }
+/// Byte ranges of `text` fitting in rows of `width` columns, the first row
+/// being `first_width` wide. Characters wider than a row are left out.
+fn wrap_rows(text: &str, first_width: usize, width: usize) -> Vec<Range<usize>> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut row_width = 0;
+    let mut limit = first_width;
+    for (i, ch) in text.char_indices() {
+        let ch_width = ch.width().unwrap_or(0);
+        if row_width + ch_width > limit {
+            // A new row, unless this one is empty and the next is no wider
+            if i > start || limit < width {
+                rows.push(start..i);
+                start = i;
+                row_width = 0;
+                limit = width;
+            }
+            if ch_width > limit {
+                start = i + ch.len_utf8();
+                continue;
             }
-        } else {
-            result.push(ch);
         }
+        row_width += ch_width;
+    }
+    if start < text.len() || rows.is_empty() {
+        rows.push(start..text.len());
+    }
+    rows
+}
+
+/// The rows a displayed line wraps to, as byte ranges of the text after the
+/// container name, which starts the first row when shown. A name as wide as
+/// the pane leaves no room for the text.
+fn line_rows(name: Option<&str>, text: &str, width: usize) -> Vec<Range<usize>> {
+    match name.map(|name| name.width() + 1) {
+        Some(prefix) if prefix >= width => std::iter::once(0..0).collect(),
+        Some(prefix) => wrap_rows(text, width - prefix, width),
+        None => wrap_rows(text, width, width),
+    }
+}
+
+/// The spans of one row of `text` in the `styles` of the output, with the
+/// parts inside `matches` highlighted on top.
+fn style_row(
+    text: &str,
+    row: Range<usize>,
+    styles: &[(Range<usize>, Style)],
+    matches: &[Range<usize>],
+    match_style: Style,
+) -> Vec<Span<'static>> {
+    // Cut the row wherever a style or a match starts or ends
+    let mut cuts = vec![row.start, row.end];
+    for range in styles.iter().map(|(range, _)| range).chain(matches) {
+        cuts.extend(
+            [range.start, range.end]
+                .into_iter()
+                .filter(|cut| row.contains(cut)),
+        );
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+    cuts.windows(2)
+        .map(|cut| {
+            let mut style = styles
+                .iter()
+                .find(|(range, _)| range.contains(&cut[0]))
+                .map_or_else(Style::default, |(_, style)| *style);
+            if matches.iter().any(|found| found.contains(&cut[0])) {
+                style = style.patch(match_style);
+            }
+            Span::styled(text[cut[0]..cut[1]].to_string(), style)
+        })
+        .collect()
+}
+
+/// Non-empty matches of the search in a displayed line.
+fn search_matches(regex: &Regex, text: &str) -> Vec<Range<usize>> {
+    regex
+        .find_iter(text)
+        .map(|found| found.range())
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/// Where the current match is among the matching lines.
+fn match_status(hits: &[usize], hit: Option<usize>) -> String {
+    match hit.and_then(|hit| hits.iter().position(|&i| i == hit)) {
+        _ if hits.is_empty() => "no matches".to_string(),
+        Some(position) => format!("match {}/{}", position + 1, hits.len()),
+        None if hits.len() == 1 => "1 matching line".to_string(),
+        None => format!("{} matching lines", hits.len()),
+    }
+}
+
+/// Compiles a pattern typed at a prompt as a regex, or as plain text when it
+/// is not a valid one, and tells which. Case-insensitive unless the pattern
+/// has an uppercase letter. `None` for an empty pattern.
+fn compile_pattern(pattern: &str) -> Option<(Regex, bool)> {
+    if pattern.is_empty() {
+        return None;
+    }
+    let case_insensitive = !pattern.chars().any(char::is_uppercase);
+    let build = |pattern: &str| {
+        RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+    };
+    match build(pattern) {
+        Ok(regex) => Some((regex, false)),
+        Err(_) => build(&regex::escape(pattern))
+            .ok()
+            .map(|regex| (regex, true)),
+    }
+}
+
+impl Search {
+    fn compile(&mut self) {
+        let compiled = compile_pattern(&self.input);
+        self.plain = compiled.as_ref().is_some_and(|(_, plain)| *plain);
+        self.regex = compiled.map(|(regex, _)| regex);
+    }
+}
+
+impl LogFilter {
+    /// `None` when nothing is left to match.
+    fn parse(input: &str) -> Option<Self> {
+        let (invert, pattern) = match input.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, input),
+        };
+        let (regex, plain) = compile_pattern(pattern)?;
+        Some(Self {
+            input: input.to_string(),
+            regex,
+            invert,
+            plain,
+        })
+    }
+
+    /// Matches the message as displayed, like `--grep` does.
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let message = ansi::strip(&entry.text[entry.message_start..]);
+        self.regex.is_match(&message) != self.invert
     }
-    result
 }
 
 impl AppState {
@@ -89,6 +301,16 @@ impl AppState {
             show_info: false,
             info_text: String::new(),
             select_all_focused: true,
+            daemon_notices: BTreeMap::new(),
+            warning: None,
+            paused: None,
+            new_lines: 0,
+            scroll_back: 0,
+            page_rows: 0,
+            search: None,
+            filter: None,
+            filter_prompt: None,
+            ansi_colors: true,
         };
         state.list_state.select(None);
         state
@@ -181,27 +403,42 @@ impl AppState {
         self.containers.iter().filter(|c| c.selected).count()
     }
 
-    fn add_log(&mut self, container_name: &str, log_line: String) {
+    fn add_log(&mut self, container_name: &str, entry: LogEntry) {
         // Add to container-specific logs
         let container_logs = self
             .container_logs
             .entry(container_name.to_string())
             .or_insert_with(|| VecDeque::with_capacity(self.max_logs));
 
-        container_logs.push_back(log_line.clone());
+        container_logs.push_back(entry.clone());
         if container_logs.len() > self.max_logs {
             container_logs.pop_front();
         }
 
-        // Update displayed logs if this container is selected
-        if self.is_container_selected(container_name) {
-            self.logs.push_back(log_line);
-            if self.logs.len() > self.max_logs {
-                self.logs.pop_front();
-            }
+        // Update displayed logs if this container is selected, keeping them in time order
+        if !self.is_container_selected(container_name) || !self.passes_filter(&entry) {
+            return;
+        }
+        let pos = self
+            .logs
+            .partition_point(|e| e.timestamp <= entry.timestamp);
+        self.logs.insert(pos, entry);
+        // A line older than every displayed one is trimmed right away
+        let kept = pos > 0 || self.logs.len() <= self.max_logs;
+        if self.logs.len() > self.max_logs {
+            self.logs.pop_front();
+        }
+        if self.paused.is_some() && kept {
+            self.new_lines += 1;
         }
     }
 
+    fn passes_filter(&self, entry: &LogEntry) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(entry))
+    }
+
     fn is_container_selected(&self, container_name: &str) -> bool {
         self.containers
             .iter()
@@ -217,13 +454,19 @@ impl AppState {
             .map(|c| c.name.clone())
             .collect();
 
-        // Merge logs from all selected containers
-        let mut all_logs: Vec<String> = Vec::new();
-        for container_name in &selected_containers {
-            if let Some(container_logs) = self.container_logs.get(container_name) {
-                all_logs.extend(container_logs.iter().cloned());
-            }
-        }
+        // Merge logs from all selected containers in chronological order
+        let sources: Vec<Vec<LogEntry>> = selected_containers
+            .iter()
+            .filter_map(|name| self.container_logs.get(name))
+            .map(|container_logs| {
+                container_logs
+                    .iter()
+                    .filter(|entry| self.passes_filter(entry))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+        let all_logs = merge_sorted_by_key(sources, |e| e.timestamp);
 
         // Take the last max_logs entries
         let start = if all_logs.len() > self.max_logs {
@@ -234,15 +477,20 @@ impl AppState {
         self.logs.extend(all_logs[start..].iter().cloned());
     }
 
-    fn add_container(&mut self, id: String, name: String) {
-        if !self.containers.iter().any(|c| c.id == id) {
+    fn add_container(&mut self, id: String, name: String, host: Option<String>) {
+        if let Some(container) = self.containers.iter_mut().find(|c| c.id == id) {
+            // A stopped container was started again
+            container.exit_code = None;
+        } else {
             let color_index = self.color_counter;
             self.color_counter += 1;
             self.containers.push(ContainerInfo {
                 id,
                 name,
+                host,
                 selected: true, // Auto-select new containers
                 color_index,
+                exit_code: None,
             });
             self.containers.sort_by(|a, b| a.name.cmp(&b.name));
             // If this is the first container, select it
@@ -260,6 +508,12 @@ impl AppState {
             .map(|c| get_color(c.color_index))
     }
 
+    fn mark_exited(&mut self, id: &str, exit_code: isize) {
+        if let Some(container) = self.containers.iter_mut().find(|c| c.id == id) {
+            container.exit_code = Some(exit_code);
+        }
+    }
+
     fn remove_container(&mut self, id: &str) {
         let removed_name = self
             .containers
@@ -301,10 +555,187 @@ impl AppState {
         self.update_displayed_logs();
     }
 
+    /// Freezes the logs pane, new lines are only counted until resuming.
+    fn pause(&mut self) {
+        if self.paused.is_none() {
+            self.paused = Some(self.logs.clone());
+            self.new_lines = 0;
+        }
+    }
+
+    /// Back to following the latest lines.
+    fn resume(&mut self) {
+        self.paused = None;
+        self.new_lines = 0;
+        self.scroll_back = 0;
+        self.clear_hit();
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.paused.is_some() {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Shows the current selection in a paused pane, keeping its scroll position.
+    fn refresh_paused(&mut self) {
+        if self.paused.is_some() {
+            self.paused = Some(self.logs.clone());
+            self.new_lines = 0;
+            self.clear_hit();
+        }
+    }
+
+    /// Scrolling back pauses, so lines arriving meanwhile do not move the view.
+    fn scroll_up(&mut self, rows: usize) {
+        self.pause();
+        self.scroll_back = self.scroll_back.saturating_add(rows);
+    }
+
+    fn scroll_down(&mut self, rows: usize) {
+        self.scroll_back = self.scroll_back.saturating_sub(rows);
+    }
+
+    /// Splits a sanitized log line into the container name, when shown and
+    /// known, and the text after it.
+    fn display_parts<'a>(
+        &self,
+        line: &'a str,
+        show_names: bool,
+    ) -> (Option<(&'a str, Color)>, &'a str) {
+        match line.split_once(' ') {
+            // Parse log line format: "container_name descriptor: log_text"
+            Some((name, rest)) if show_names => match self.get_container_color(name) {
+                Some(color) => (Some((name, color)), rest),
+                None => (None, line),
+            },
+            // Only one container selected, skip container name
+            Some((_, rest)) => (None, rest),
+            None => (None, line),
+        }
+    }
+
+    /// Displayed lines matching the search, oldest first.
+    fn search_hits(&self) -> Vec<usize> {
+        let regex = match self
+            .search
+            .as_ref()
+            .and_then(|search| search.regex.as_ref())
+        {
+            Some(regex) => regex,
+            None => return Vec::new(),
+        };
+        let show_names = self.selected_count() != 1;
+        self.paused
+            .as_ref()
+            .unwrap_or(&self.logs)
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                let line = ansi::strip(&entry.text);
+                let (_, text) = self.display_parts(&line, show_names);
+                !search_matches(regex, text).is_empty()
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Moves to the next older or newer search match, wrapping around. The
+    /// pane pauses so the match stays where it is.
+    fn jump_to_match(&mut self, older: bool) {
+        let hits = self.search_hits();
+        if hits.is_empty() {
+            self.clear_hit();
+            return;
+        }
+        // Pausing copies the displayed lines, the hits stay valid
+        self.pause();
+        if let Some(search) = self.search.as_mut() {
+            search.hit = match search.hit {
+                Some(current) if older => hits.iter().rev().find(|&&i| i < current).or(hits.last()),
+                Some(current) => hits.iter().find(|&&i| i > current).or(hits.first()),
+                // Searching starts from the latest line
+                None => hits.last(),
+            }
+            .copied();
+            search.reveal = true;
+        }
+    }
+
+    /// Forgets the current match when the lines it points into change.
+    fn clear_hit(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.hit = None;
+        }
+    }
+
+    fn set_filter(&mut self, filter: Option<LogFilter>) {
+        self.filter = filter;
+        self.update_displayed_logs();
+        self.refresh_paused();
+    }
+
+    /// Edits the filter prompt, filtering as the input changes.
+    fn filter_key(&mut self, code: KeyCode) {
+        let (input, previous) = match self.filter_prompt.as_mut() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        match code {
+            KeyCode::Esc => {
+                let previous = previous.take();
+                self.filter_prompt = None;
+                self.set_filter(previous);
+            }
+            KeyCode::Enter => self.filter_prompt = None,
+            KeyCode::Backspace => {
+                input.pop();
+                let filter = LogFilter::parse(input);
+                self.set_filter(filter);
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                let filter = LogFilter::parse(input);
+                self.set_filter(filter);
+            }
+            _ => {}
+        }
+    }
+
+    /// Edits the search prompt, searching as the input changes.
+    fn search_key(&mut self, code: KeyCode) {
+        let search = match self.search.as_mut() {
+            Some(search) => search,
+            None => return,
+        };
+        match code {
+            KeyCode::Esc => self.search = None,
+            KeyCode::Enter if search.input.is_empty() => self.search = None,
+            KeyCode::Enter => search.typing = false,
+            KeyCode::Backspace if search.input.is_empty() => self.search = None,
+            KeyCode::Backspace => {
+                search.input.pop();
+                search.compile();
+                search.hit = None;
+                self.jump_to_match(true);
+            }
+            KeyCode::Char(c) => {
+                search.input.push(c);
+                search.compile();
+                search.hit = None;
+                self.jump_to_match(true);
+            }
+            _ => {}
+        }
+    }
+
     fn max_container_name_width(&self) -> u16 {
         self.containers
             .iter()
-            .map(|c| c.name.len())
+            // Leave room for the " ✗<code>" exited marker
+            .map(|c| c.name.len() + c.exit_code.map_or(0, |code| code.to_string().len() + 2))
             .max()
             .unwrap_or(20)
             .max(20) as u16
@@ -384,6 +815,9 @@ fn ui(f: &mut Frame, app: &mut AppState) {
         ])
     };
 
+    // Displayed lines with a search match
+    let mut hits = Vec::new();
+
     // Right pane: logs or info (render FIRST to prevent overflow)
     if app.show_info {
         let info_paragraph = Paragraph::new(app.info_text.as_str())
@@ -418,151 +852,125 @@ fn ui(f: &mut Frame, app: &mut AppState) {
             1
         };
 
-        let log_text: Vec<Line> = app
-            .logs
-            .iter()
-            .flat_map(|line| {
-                // Sanitize the line - remove control characters and ANSI codes that mess up display
-                let without_ansi = strip_ansi_codes(line);
-                let sanitized = without_ansi
-                    .chars()
-                    .filter(|c| !c.is_control() || *c == ' ')
-                    .collect::<String>()
-                    .replace('\r', "")
-                    .replace('\n', " ")
-                    .replace('\t', "    ");
-
-                let line = &sanitized;
-
-                // First, build the full line
-                let full_line = if show_container_names {
-                    // Parse log line format: "container_name descriptor: log_text"
-                    if let Some(first_space_idx) = line.find(' ') {
-                        let container_name = &line[..first_space_idx];
-                        let rest = &line[first_space_idx..];
-
-                        if let Some(color) = app.get_container_color(container_name) {
-                            (
-                                Some(container_name.to_string()),
-                                Some(color),
-                                rest.to_string(),
-                            )
-                        } else {
-                            (None, None, line.clone())
-                        }
-                    } else {
-                        (None, None, line.clone())
-                    }
-                } else {
-                    // Only one container selected, skip container name
-                    // Format: "container_name descriptor: log_text" -> "descriptor: log_text"
-                    if let Some(first_space_idx) = line.find(' ') {
-                        (None, None, line[first_space_idx + 1..].to_string())
-                    } else {
-                        (None, None, line.clone())
-                    }
-                };
-
-                // Wrap text to fit within available width - can wrap multiple times
-                let (container_name, color, rest) = full_line;
-                let mut wrapped_lines = Vec::new();
-
-                if max_width == 0 {
-                    return wrapped_lines;
-                }
-
-                if let (Some(name), Some(c)) = (container_name, color) {
-                    let prefix_width = name.width();
-                    let remaining_width = max_width.saturating_sub(prefix_width).saturating_sub(1);
-
-                    if remaining_width == 0 {
-                        wrapped_lines.push(Line::from(vec![Span::styled(
-                            name,
-                            Style::default().fg(c).add_modifier(Modifier::BOLD),
-                        )]));
-                        return wrapped_lines;
-                    }
-
-                    // First line with container name
-                    let mut first_line_text = String::new();
-                    let mut current_width = 0;
-                    for ch in rest.chars() {
-                        let ch_width = ch.width().unwrap_or(0);
-                        if current_width + ch_width > remaining_width {
-                            break;
-                        }
-                        first_line_text.push(ch);
-                        current_width += ch_width;
-                    }
-                    let first_line_len = first_line_text.len();
-
-                    wrapped_lines.push(Line::from(vec![
-                        Span::styled(name, Style::default().fg(c).add_modifier(Modifier::BOLD)),
-                        Span::raw(first_line_text),
-                    ]));
-
-                    // Additional wrapped lines
-                    let mut remaining = &rest[first_line_len..];
-                    while !remaining.is_empty() {
-                        let mut chunk = String::new();
-                        let mut current_width = 0;
-                        let mut chars_consumed = 0;
-
-                        for ch in remaining.chars() {
-                            let ch_width = ch.width().unwrap_or(0);
-                            if current_width + ch_width > max_width {
-                                break;
-                            }
-                            chunk.push(ch);
-                            current_width += ch_width;
-                            chars_consumed += ch.len_utf8();
-                        }
+        let block_height = chunks[1].height.saturating_sub(2) as usize; // Account for borders
+        app.page_rows = block_height;
+        if app.search.is_some() {
+            hits = app.search_hits();
+        }
+        let reveal = app
+            .search
+            .as_mut()
+            .filter(|search| search.reveal)
+            .and_then(|search| {
+                search.reveal = false;
+                search.hit
+            });
 
-                        if chunk.is_empty() && !remaining.is_empty() {
-                            // Skip character that's too wide
-                            let first_char = remaining.chars().next().unwrap();
-                            chars_consumed = first_char.len_utf8();
-                        }
+        // Lay the lines out from the latest one up, only as far back as the
+        // pane is scrolled, or as the search match to bring into view
+        let logs = app.paused.as_ref().unwrap_or(&app.logs);
+        let mut laid_out = VecDeque::new();
+        let mut laid_out_rows = 0;
+        let mut wanted_rows = app.scroll_back.saturating_add(block_height);
+        // Rows from the bottom up to the first one of the search match
+        let mut hit_rows = None;
+        for (index, entry) in logs.iter().enumerate().rev() {
+            if laid_out_rows >= wanted_rows && reveal.is_none_or(|hit| index < hit) {
+                break;
+            }
+            // Take out escape sequences and control characters that mess up display
+            let mut line = StyledText::parse(&entry.text);
+            if !app.ansi_colors {
+                line.styles.clear();
+            }
+            let (name, text) = app.display_parts(&line.text, show_container_names);
+            let rows = line_rows(name.map(|(name, _)| name), text, max_width);
+            laid_out_rows += rows.len();
+            if reveal == Some(index) {
+                hit_rows = Some(laid_out_rows);
+                // Enough rows above the match to center it
+                wanted_rows = wanted_rows.max(laid_out_rows + block_height);
+            }
+            laid_out.push_front((index, line, rows));
+        }
 
-                        if !chunk.is_empty() {
-                            wrapped_lines.push(Line::from(chunk));
-                        }
-                        remaining = &remaining[chars_consumed..];
-                    }
-                } else {
-                    // No container name, wrap the text
-                    let mut remaining = rest.as_str();
-                    while !remaining.is_empty() {
-                        let mut chunk = String::new();
-                        let mut current_width = 0;
-                        let mut chars_consumed = 0;
-
-                        for ch in remaining.chars() {
-                            let ch_width = ch.width().unwrap_or(0);
-                            if current_width + ch_width > max_width {
-                                break;
-                            }
-                            chunk.push(ch);
-                            current_width += ch_width;
-                            chars_consumed += ch.len_utf8();
-                        }
+        // Show the latest logs at the bottom, unless scrolled back. Unless
+        // every line was laid out, there are more rows above than needed.
+        let max_scroll = laid_out_rows.saturating_sub(block_height);
+        app.scroll_back = app.scroll_back.min(max_scroll);
+        // Bring the current search match into view
+        if let Some(hit_rows) = hit_rows {
+            let row = hit_rows - 1;
+            if row < app.scroll_back || row >= app.scroll_back + block_height {
+                app.scroll_back = (row + 1 + block_height / 2)
+                    .saturating_sub(block_height)
+                    .min(max_scroll);
+            }
+        }
+        // The rows on screen, counted from the top of the laid out lines
+        let visible = laid_out_rows.saturating_sub(app.scroll_back + block_height)
+            ..laid_out_rows - app.scroll_back;
+
+        let search = app.search.as_ref();
+        let regex = search.and_then(|search| search.regex.as_ref());
+        let current_hit = search.and_then(|search| search.hit);
+        let match_style = Style::default().fg(Color::Black).bg(Color::Yellow);
+        let current_match_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Magenta)
+            .add_modifier(Modifier::BOLD);
+
+        let mut log_text: Vec<Line> = Vec::with_capacity(block_height);
+        let mut first_row = 0;
+        for (index, line, rows) in &laid_out {
+            let line_start = first_row;
+            first_row += rows.len();
+            if first_row <= visible.start || line_start >= visible.end {
+                continue;
+            }
+            let (name, text) = app.display_parts(&line.text, show_container_names);
+            // The styles of the output, relative to the text after the name
+            let offset = line.text.len() - text.len();
+            let styles: Vec<(Range<usize>, Style)> = line
+                .styles
+                .iter()
+                .filter(|(range, _)| range.end > offset)
+                .map(|(range, style)| {
+                    (
+                        range.start.saturating_sub(offset)..range.end - offset,
+                        *style,
+                    )
+                })
+                .collect();
 
-                        if chunk.is_empty() && !remaining.is_empty() {
-                            // Skip character that's too wide
-                            let first_char = remaining.chars().next().unwrap();
-                            chars_consumed = first_char.len_utf8();
-                        }
+            let matches = regex.map_or_else(Vec::new, |regex| search_matches(regex, text));
+            let match_style = if current_hit == Some(*index) {
+                current_match_style
+            } else {
+                match_style
+            };
 
-                        if !chunk.is_empty() {
-                            wrapped_lines.push(Line::from(chunk));
-                        }
-                        remaining = &remaining[chars_consumed..];
+            for (i, row) in rows.iter().enumerate() {
+                if !visible.contains(&(line_start + i)) {
+                    continue;
+                }
+                let mut spans = Vec::new();
+                // First line with container name
+                if let Some((name, color)) = name.filter(|_| i == 0) {
+                    spans.push(Span::styled(
+                        name.to_string(),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ));
+                    if name.width() + 1 >= max_width {
+                        log_text.push(Line::from(spans));
+                        continue;
                     }
+                    spans.push(Span::raw(" "));
                 }
-
-                wrapped_lines
-            })
-            .collect();
+                spans.extend(style_row(text, row.clone(), &styles, &matches, match_style));
+                log_text.push(Line::from(spans));
+            }
+        }
 
         // Final safety check: ensure no line exceeds max width
         // Use the SAME conservative width as truncation to ensure consistency
@@ -609,14 +1017,53 @@ fn ui(f: &mut Frame, app: &mut AppState) {
             })
             .collect();
 
-        // Calculate scroll to show latest logs at bottom
-        let block_height = chunks[1].height.saturating_sub(2); // Account for borders
-        let log_count = log_text.len();
-        let scroll_offset = if log_count > block_height as usize {
-            (log_count - block_height as usize) as u16
-        } else {
-            0
-        };
+        let mut title = vec![Span::raw("▶ LOGS")];
+        if app.paused.is_some() {
+            let lines = if app.new_lines == 1 { "line" } else { "lines" };
+            title.push(Span::styled(
+                format!(" ⏸ paused, {} new {} (t: live tail)", app.new_lines, lines),
+                Style::default().fg(Color::LightYellow),
+            ));
+        }
+        if let Some(filter) = &app.filter {
+            title.push(Span::styled(
+                format!(" filter: {}", filter.input),
+                Style::default().fg(Color::LightGreen),
+            ));
+        }
+        if let Some(search) = app.search.as_ref().filter(|search| !search.typing) {
+            title.push(Span::styled(
+                format!(" /{} {}", search.input, match_status(&hits, search.hit)),
+                Style::default().fg(Color::LightCyan),
+            ));
+        }
+        for (host, notice) in &app.daemon_notices {
+            let daemon = match host {
+                Some(host) => format!("Docker daemon {}", host),
+                None => "Docker daemon".to_string(),
+            };
+            match notice {
+                DaemonNotice::Disconnected => title.push(Span::styled(
+                    format!(" ✗ {} disconnected, reconnecting...", daemon),
+                    Style::default().fg(Color::LightRed),
+                )),
+                DaemonNotice::Reconnected(at) if at.elapsed() < RECONNECTED_NOTICE_DURATION => {
+                    title.push(Span::styled(
+                        format!(" ✓ {} reconnected", daemon),
+                        Style::default().fg(Color::LightGreen),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if let Some((warning, at)) = &app.warning {
+            if at.elapsed() < WARNING_DURATION {
+                title.push(Span::styled(
+                    format!(" ! {}", warning),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+        }
 
         let paragraph = Paragraph::new(log_text)
             .block(
@@ -627,15 +1074,14 @@ fn ui(f: &mut Frame, app: &mut AppState) {
                             .fg(Color::Cyan)
                             .add_modifier(Modifier::BOLD),
                     )
-                    .title("▶ LOGS")
+                    .title(Line::from(title))
                     .title_style(
                         Style::default()
                             .fg(Color::Magenta)
                             .add_modifier(Modifier::BOLD),
                     ),
             )
-            .alignment(Alignment::Left)
-            .scroll((scroll_offset, 0));
+            .alignment(Alignment::Left);
 
         f.render_widget(paragraph, chunks[1]);
     }
@@ -664,14 +1110,20 @@ fn ui(f: &mut Frame, app: &mut AppState) {
                 Style::default().fg(Color::DarkGray)
             };
             let color = get_color(c.color_index);
-            let line = Line::from(vec![
+            let mut spans = vec![
                 Span::styled(format!("{} ", checkbox), checkbox_style),
                 Span::styled(
                     &c.name,
                     Style::default().fg(color).add_modifier(Modifier::BOLD),
                 ),
-            ]);
-            ListItem::new(line)
+            ];
+            if let Some(code) = c.exit_code {
+                spans.push(Span::styled(
+                    format!(" ✗{}", code),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -702,21 +1154,77 @@ fn ui(f: &mut Frame, app: &mut AppState) {
     f.render_stateful_widget(list, left_chunks[1], &mut app.list_state);
 
     // Help line at bottom
-    let help_text = if app.show_info {
-        "↑/↓: Navigate | Enter/Space: Toggle | i: Close Info | a: All | n: None | Esc/q: Quit"
+    let mut keys = vec!["↑/↓: Navigate", "Space: Toggle"];
+    if app.search.is_some() {
+        keys.extend(["n/N: Older/Newer Match", "Esc: Clear Search"]);
     } else {
-        "↑/↓: Navigate | Enter/Space: Toggle | i: Show Info | a: All | n: None | Esc/q: Quit"
-    };
-
-    let help_spans = vec![
-        Span::styled(
-            "◆ ",
+        keys.push(if app.show_info {
+            "i: Close Info"
+        } else {
+            "i: Show Info"
+        });
+        keys.extend(["a: All", "n: None"]);
+    }
+    keys.extend(["/: Search", "f: Filter"]);
+    if app.filter.is_some() {
+        keys.push("F: Clear Filter");
+    }
+    keys.push(if app.ansi_colors {
+        "c: Strip Colours"
+    } else {
+        "c: Show Colours"
+    });
+    keys.extend([
+        "PgUp/PgDn/Home/End: Scroll",
+        "p: Pause",
+        "t: Live",
+        "q: Quit",
+    ]);
+    let help_text = keys.join(" | ");
+
+    let mut help_spans = vec![Span::styled(
+        "◆ ",
+        Style::default()
+            .fg(Color::Magenta)
+            .add_modifier(Modifier::BOLD),
+    )];
+    // The prompts take the help line while typing
+    if let Some((input, _)) = &app.filter_prompt {
+        help_spans.push(Span::styled(
+            format!("filter: {}█", input),
             Style::default()
-                .fg(Color::Magenta)
+                .fg(Color::LightGreen)
                 .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(help_text, Style::default().fg(Color::Cyan)),
-    ];
+        ));
+        if app.filter.as_ref().is_some_and(|filter| filter.plain) {
+            help_spans.push(Span::styled(
+                "  (plain text)",
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        help_spans.push(Span::styled(
+            "  !: Not Matching | Enter: Keep | Esc: Cancel",
+            Style::default().fg(Color::Cyan),
+        ));
+    } else if let Some(search) = app.search.as_ref().filter(|search| search.typing) {
+        help_spans.push(Span::styled(
+            format!("/{}█", search.input),
+            Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+        ));
+        let mut status = format!("  {}", match_status(&hits, search.hit));
+        if search.plain {
+            status.push_str(" (plain text)");
+        }
+        help_spans.push(Span::styled(status, Style::default().fg(Color::Yellow)));
+        help_spans.push(Span::styled(
+            "  Enter: Keep | Esc: Cancel",
+            Style::default().fg(Color::Cyan),
+        ));
+    } else {
+        help_spans.push(Span::styled(help_text, Style::default().fg(Color::Cyan)));
+    }
 
     let help_widget = Paragraph::new(Line::from(help_spans)).block(
         Block::default().borders(Borders::ALL).border_style(
@@ -728,11 +1236,8 @@ fn ui(f: &mut Frame, app: &mut AppState) {
     f.render_widget(help_widget, main_chunks[1]);
 }
 
-async fn get_container_info(docker_url: &str, container_id: &str) -> String {
-    let docker = crate::get_docker(docker_url).await;
-    let container = docker_api::container::Container::new(docker, container_id.to_string());
-
-    match container.inspect().await {
+async fn get_container_info(backend: &dyn Backend, container_id: &str) -> String {
+    match backend.inspect(container_id).await {
         Ok(info) => {
             let mut output = String::new();
             output.push_str(&format!("ID: {}\n", container_id));
@@ -774,228 +1279,263 @@ async fn get_container_info(docker_url: &str, container_id: &str) -> String {
     }
 }
 
-async fn log_container(
-    docker_url: String,
-    container_id: String,
-    container_regex: regex::Regex,
-    last_n_lines: usize,
+/// Formats a log line for the logs pane.
+fn format_line(name: &str, time: &TimeArgs, event: LogEvent) -> LogEntry {
+    let mut text = format!("{} {}: ", name, event.stream.descriptor());
+    if let Some(ts) = event.timestamp.as_ref().filter(|_| time.timestamps) {
+        text.push_str(&format!("{} ", time.format(ts)));
+    }
+    let message_start = text.len();
+    text.push_str(&event.message());
+    LogEntry {
+        timestamp: event.timestamp,
+        text,
+        message_start,
+    }
+}
+
+/// Applies the events of every daemon to the app state.
+async fn apply_events(
+    mut subscription: Subscription,
     app_state: Arc<Mutex<AppState>>,
+    time: TimeArgs,
 ) {
-    let docker = crate::get_docker(&docker_url).await;
-    let container = docker_api::container::Container::new(docker, container_id.clone());
+    while let Some(event) = subscription.next().await {
+        let mut app = app_state.lock().await;
+        match event {
+            SourceEvent::Started(meta) => {
+                app.add_container(meta.id.clone(), meta.display_name(), meta.host.clone())
+            }
+            SourceEvent::Log(event) => {
+                let name = event.container.display_name();
+                app.add_log(&name, format_line(&name, &time, event));
+            }
+            SourceEvent::Stopped {
+                container,
+                exit_code,
+                error,
+            } => {
+                if let Some(err) = error {
+                    app.warning = Some((err.to_string(), Instant::now()));
+                }
+                // Container stopped, keep it listed as exited when showing all containers
+                match exit_code {
+                    Some(code) => app.mark_exited(&container.id, code),
+                    None => app.remove_container(&container.id),
+                }
+            }
+            SourceEvent::Removed(container) => app.remove_container(&container.id),
+            SourceEvent::Disconnected { host } => {
+                app.daemon_notices.insert(host, DaemonNotice::Disconnected);
+            }
+            SourceEvent::Reconnected { host } => {
+                app.daemon_notices
+                    .insert(host, DaemonNotice::Reconnected(Instant::now()));
+            }
+        }
+    }
+}
 
-    let info = match container.inspect().await {
-        Ok(info) => info,
-        Err(_) => return,
-    };
+/// The TUI's state, fed by the log sources of every daemon and driven by
+/// terminal events.
+///
+/// [`run_tui`] drives it with a real terminal; tests can draw it on any
+/// ratatui backend and feed it events of their own.
+pub struct App {
+    state: Arc<Mutex<AppState>>,
+    /// Backend of each daemon by alias, to inspect containers
+    backends: HashMap<Option<String>, Arc<dyn Backend>>,
+}
 
-    let name = match &info.name {
-        Some(n) => {
-            if let Some(stripped) = n.strip_prefix('/') {
-                stripped.to_owned()
-            } else {
-                n.clone()
+impl App {
+    /// Starts following the selected containers of every daemon.
+    pub async fn start(
+        hosts: Vec<(Arc<dyn Backend>, Endpoint)>,
+        selector: &Selector,
+        options: SourceOptions,
+    ) -> error::Result<Self> {
+        let max_logs = options.last_n_lines.map_or(WINDOW_MAX_LOGS, |n| n * 10);
+        let state = Arc::new(Mutex::new(AppState::new(max_logs)));
+        let time = options.time.clone();
+
+        let single_host = hosts.len() == 1;
+        let (sink, subscription) = source::channel();
+        let mut backends = HashMap::new();
+        for (backend, endpoint) in hosts {
+            backends.insert(endpoint.alias.clone(), backend.clone());
+            let source = LogSource::new(backend, &endpoint, selector.clone(), options.clone());
+            match source.start(&sink).await {
+                Ok(()) => {}
+                Err(err) if single_host => return Err(err),
+                // One of several daemons being down is shown in the title, the
+                // source keeps retrying it
+                Err(_) => {
+                    let mut app = state.lock().await;
+                    app.daemon_notices
+                        .insert(endpoint.alias.clone(), DaemonNotice::Disconnected);
+                }
             }
         }
-        None => return,
-    };
 
-    if container_regex.find(&name).is_none() {
-        return;
+        tokio::spawn(apply_events(subscription, state.clone(), time));
+        Ok(Self { state, backends })
     }
 
-    // Add container to the list
-    {
-        let mut app = app_state.lock().await;
-        app.add_container(container_id.clone(), name.clone());
-    }
-
-    let log_opts = docker_api::opts::LogsOpts::builder()
-        .follow(true)
-        .n_lines(last_n_lines)
-        .stdout(true)
-        .stderr(true)
-        .timestamps(false)
-        .build();
-
-    let mut stream = container.logs(&log_opts);
-    while let Some(data) = stream.next().await {
-        match data {
-            Ok(contents) => {
-                let (descriptor, line) = match contents {
-                    docker_api::conn::TtyChunk::StdIn(inner) => {
-                        ("i", String::from_utf8_lossy(&inner).into_owned())
-                    }
-                    docker_api::conn::TtyChunk::StdOut(inner) => {
-                        ("o", String::from_utf8_lossy(&inner).into_owned())
-                    }
-                    docker_api::conn::TtyChunk::StdErr(inner) => {
-                        ("e", String::from_utf8_lossy(&inner).into_owned())
-                    }
-                };
-                let log_line = format!("{} {}: {}", name, descriptor, line.trim());
-                let mut app = app_state.lock().await;
-                app.add_log(&name, log_line);
-            }
-            Err(_) => break,
+    /// Draws the current state, with any captured warning in the title.
+    pub async fn draw<B: ratatui::backend::Backend>(
+        &self,
+        terminal: &mut Terminal<B>,
+    ) -> io::Result<()> {
+        let mut app = self.state.lock().await;
+        if let Some(warning) = error::take_warnings().pop() {
+            app.warning = Some((warning, Instant::now()));
         }
+        terminal.draw(|f| ui(f, &mut app))?;
+        Ok(())
     }
 
-    // Container stopped
-    {
-        let mut app = app_state.lock().await;
-        app.remove_container(&container_id);
+    /// Handles a terminal event. Returns false once the user quits.
+    pub async fn handle_event(&self, event: Event) -> bool {
+        let key = match event {
+            Event::Key(key) => key,
+            Event::Mouse(mouse) => {
+                let mut app = self.state.lock().await;
+                match mouse.kind {
+                    MouseEventKind::ScrollUp => app.scroll_up(WHEEL_SCROLL_ROWS),
+                    MouseEventKind::ScrollDown => app.scroll_down(WHEEL_SCROLL_ROWS),
+                    _ => {}
+                }
+                return true;
+            }
+            _ => return true,
+        };
+        let mut app = self.state.lock().await;
+        if app.filter_prompt.is_some() {
+            app.filter_key(key.code);
+            return true;
+        }
+        if app.search.as_ref().is_some_and(|search| search.typing) {
+            app.search_key(key.code);
+            return true;
+        }
+        match key.code {
+            KeyCode::Esc if app.search.is_some() => app.search = None,
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => {
+                app.search = Some(Search {
+                    typing: true,
+                    ..Search::default()
+                })
+            }
+            KeyCode::Char('f') => {
+                let input = app
+                    .filter
+                    .as_ref()
+                    .map_or_else(String::new, |filter| filter.input.clone());
+                app.filter_prompt = Some((input, app.filter.clone()));
+            }
+            KeyCode::Char('F') => app.set_filter(None),
+            KeyCode::Char('c') => app.ansi_colors = !app.ansi_colors,
+            KeyCode::Char('n') if app.search.is_some() => app.jump_to_match(true),
+            KeyCode::Char('N') if app.search.is_some() => app.jump_to_match(false),
+            KeyCode::Down | KeyCode::Char('j') => app.next(),
+            KeyCode::Up | KeyCode::Char('k') => app.previous(),
+            KeyCode::Char(' ') | KeyCode::Enter => {
+                app.toggle_selected();
+                app.refresh_paused();
+            }
+            KeyCode::Char('a') => {
+                app.select_all();
+                app.refresh_paused();
+            }
+            KeyCode::Char('n') => {
+                app.deselect_all();
+                app.refresh_paused();
+            }
+            KeyCode::PageUp => {
+                let rows = app.page_rows.max(1);
+                app.scroll_up(rows);
+            }
+            KeyCode::PageDown => {
+                let rows = app.page_rows.max(1);
+                app.scroll_down(rows);
+            }
+            // Clamped to the oldest line when drawing
+            KeyCode::Home => app.scroll_up(usize::MAX),
+            KeyCode::End => app.scroll_back = 0,
+            KeyCode::Char('p') => app.toggle_pause(),
+            KeyCode::Char('t') => app.resume(),
+            KeyCode::Char('i') => {
+                if app.show_info {
+                    // Close info panel
+                    app.show_info = false;
+                } else if let Some(selected_idx) = app.list_state.selected() {
+                    // Show info for selected container
+                    if selected_idx < app.containers.len() {
+                        let container = &app.containers[selected_idx];
+                        let container_id = container.id.clone();
+                        let backend = self.backends[&container.host].clone();
+                        drop(app); // Release lock before async operation
+
+                        let info = get_container_info(backend.as_ref(), &container_id).await;
+
+                        let mut app = self.state.lock().await;
+                        app.info_text = info;
+                        app.show_info = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+        true
     }
 }
 
 pub async fn run_tui(
-    url: &str,
-    container_regex_str: &str,
-    last_n_lines: usize,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Setup terminal
+    hosts: Vec<(Arc<dyn Backend>, Endpoint)>,
+    selector: &Selector,
+    options: SourceOptions,
+) -> error::Result<()> {
+    // Start before taking over the terminal so connection errors print normally
+    let app = App::start(hosts, selector, options).await?;
+
+    // Setup terminal, warnings would garble the screen and go to the title instead
+    error::capture_warnings();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app_state = Arc::new(Mutex::new(AppState::new(last_n_lines * 10)));
-    let docker = crate::get_docker(url).await;
-    let container_regex = regex::Regex::new(container_regex_str)?;
-
-    // Spawn container log tasks
-    let containers = docker.containers().list(&Default::default()).await?;
-    for container_info in containers {
-        let container_id = match &container_info.id {
-            Some(id) => id.clone(),
-            None => continue,
-        };
-
-        let docker_url = url.to_string();
-        let regex = container_regex.clone();
-        let app = app_state.clone();
-
-        tokio::spawn(async move {
-            log_container(docker_url, container_id, regex, last_n_lines, app).await;
-        });
-    }
-
-    // Spawn event monitoring task
-    let event_app_state = app_state.clone();
-    let event_url = url.to_string();
-    let event_regex = container_regex.clone();
-    tokio::spawn(async move {
-        let event_docker = crate::get_docker(&event_url).await;
-        let event_opts = docker_api::opts::EventsOpts::builder().build();
-        let mut events = event_docker.events(&event_opts);
-
-        while let Some(event_result) = events.next().await {
-            if let Ok(event) = event_result {
-                if event.type_.as_deref() == Some("container")
-                    && event.action.as_deref() == Some("start")
-                {
-                    if let Some(container_id) = event.actor.and_then(|a| a.id) {
-                        let docker_url = event_url.clone();
-                        let regex = event_regex.clone();
-                        let app = event_app_state.clone();
-
-                        tokio::spawn(async move {
-                            log_container(docker_url, container_id, regex, last_n_lines, app).await;
-                        });
-                    }
-                }
-            }
-        }
-    });
-
     // Main UI loop
-    let docker_url_clone = url.to_string();
-    tokio::task::spawn_blocking(
-        move || -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-            loop {
-                // Render UI
-                {
-                    let mut app = tokio::runtime::Handle::current().block_on(app_state.lock());
-                    terminal.draw(|f| ui(f, &mut app))?;
-                }
+    loop {
+        app.draw(&mut terminal).await?;
 
-                // Handle input
-                if event::poll(std::time::Duration::from_millis(100))? {
-                    if let Event::Key(key) = event::read()? {
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => break,
-                            KeyCode::Down | KeyCode::Char('j') => {
-                                let mut app =
-                                    tokio::runtime::Handle::current().block_on(app_state.lock());
-                                app.next();
-                            }
-                            KeyCode::Up | KeyCode::Char('k') => {
-                                let mut app =
-                                    tokio::runtime::Handle::current().block_on(app_state.lock());
-                                app.previous();
-                            }
-                            KeyCode::Char(' ') | KeyCode::Enter => {
-                                let mut app =
-                                    tokio::runtime::Handle::current().block_on(app_state.lock());
-                                app.toggle_selected();
-                            }
-                            KeyCode::Char('a') => {
-                                let mut app =
-                                    tokio::runtime::Handle::current().block_on(app_state.lock());
-                                app.select_all();
-                            }
-                            KeyCode::Char('n') => {
-                                let mut app =
-                                    tokio::runtime::Handle::current().block_on(app_state.lock());
-                                app.deselect_all();
-                            }
-                            KeyCode::Char('i') => {
-                                let mut app =
-                                    tokio::runtime::Handle::current().block_on(app_state.lock());
-
-                                if app.show_info {
-                                    // Close info panel
-                                    app.show_info = false;
-                                } else if let Some(selected_idx) = app.list_state.selected() {
-                                    // Show info for selected container
-                                    if selected_idx < app.containers.len() {
-                                        let container_id = app.containers[selected_idx].id.clone();
-                                        let docker_url = docker_url_clone.clone();
-                                        drop(app); // Release lock before async operation
-
-                                        let info = tokio::runtime::Handle::current().block_on(
-                                            get_container_info(&docker_url, &container_id),
-                                        );
-
-                                        let mut app = tokio::runtime::Handle::current()
-                                            .block_on(app_state.lock());
-                                        app.info_text = info;
-                                        app.show_info = true;
-                                    }
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                }
+        // Handle input, polling blocks so it runs off the async workers
+        let input = tokio::task::spawn_blocking(|| -> io::Result<Option<Event>> {
+            if event::poll(Duration::from_millis(100))? {
+                Ok(Some(event::read()?))
+            } else {
+                Ok(None)
             }
+        })
+        .await
+        .map_err(io::Error::other)??;
+        if let Some(event) = input {
+            if !app.handle_event(event).await {
+                break;
+            }
+        }
+    }
 
-            // Restore terminal
-            disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
-            terminal.show_cursor()?;
-
-            Ok(())
-        },
-    )
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?
-    .map_err(|e| format!("{}", e))?;
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
 
     Ok(())
 }
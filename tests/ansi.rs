@@ -0,0 +1,189 @@
+use dockerlogs::ansi::{self, StyledText};
+use ratatui::style::{Color, Modifier, Style};
+use std::borrow::Cow;
+
+/// The styled parts of a parsed line, as text and style.
+fn runs(input: &str) -> Vec<(String, Style)> {
+    let parsed = StyledText::parse(input);
+    parsed
+        .styles
+        .iter()
+        .map(|(range, style)| (parsed.text[range.clone()].to_string(), *style))
+        .collect()
+}
+
+#[test]
+fn plain_text_is_left_alone() {
+    let parsed = StyledText::parse("GET /orders 200 ✓");
+    assert_eq!(parsed.text, "GET /orders 200 ✓");
+    assert!(parsed.styles.is_empty());
+}
+
+#[test]
+fn basic_and_bright_colours() {
+    let parsed = StyledText::parse("\x1b[31mred\x1b[0m \x1b[94;42mblue on green\x1b[m done");
+    assert_eq!(parsed.text, "red blue on green done");
+    assert_eq!(
+        runs("\x1b[31mred\x1b[0m \x1b[94;42mblue on green\x1b[m done"),
+        [
+            ("red".to_string(), Style::default().fg(Color::Red)),
+            (
+                "blue on green".to_string(),
+                Style::default().fg(Color::LightBlue).bg(Color::Green)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn palette_and_truecolor() {
+    assert_eq!(
+        runs("\x1b[38;5;208morange\x1b[48;2;10;20;30m on navy"),
+        [
+            (
+                "orange".to_string(),
+                Style::default().fg(Color::Indexed(208))
+            ),
+            (
+                " on navy".to_string(),
+                Style::default()
+                    .fg(Color::Indexed(208))
+                    .bg(Color::Rgb(10, 20, 30))
+            ),
+        ]
+    );
+    // The colon form, with and without a colour space id
+    assert_eq!(
+        runs("\x1b[38:2::1:2:3ma\x1b[38:2:4:5:6mb\x1b[48:5:17mc"),
+        [
+            ("a".to_string(), Style::default().fg(Color::Rgb(1, 2, 3))),
+            ("b".to_string(), Style::default().fg(Color::Rgb(4, 5, 6))),
+            (
+                "c".to_string(),
+                Style::default()
+                    .fg(Color::Rgb(4, 5, 6))
+                    .bg(Color::Indexed(17))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn modifiers_and_their_resets() {
+    assert_eq!(
+        runs("\x1b[1mbold \x1b[4mand underlined\x1b[22m underlined\x1b[24m plain"),
+        [
+            (
+                "bold ".to_string(),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+            (
+                "and underlined".to_string(),
+                Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+            ),
+            (
+                " underlined".to_string(),
+                Style::default().add_modifier(Modifier::UNDERLINED)
+            ),
+        ]
+    );
+    // Resetting the colours alone keeps the rest
+    assert_eq!(
+        runs("\x1b[1;33mwarn\x1b[39m!"),
+        [
+            (
+                "warn".to_string(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            ),
+            (
+                "!".to_string(),
+                Style::default().add_modifier(Modifier::BOLD)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn drops_cursor_movement_and_osc() {
+    assert_eq!(
+        ansi::strip("\x1b[2K\x1b[1Gprogress\x1b[?25l 50%\x1b[5A"),
+        "progress 50%"
+    );
+    assert_eq!(ansi::strip("\x1b]0;title\x07after"), "after");
+    assert_eq!(
+        ansi::strip("see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\ here"),
+        "see docs here"
+    );
+}
+
+#[test]
+fn drops_control_characters() {
+    assert_eq!(ansi::strip("a\tb\r\nc\x07\x08"), "a    b c");
+    // A sequence cut off at the end of the line leaves nothing behind
+    assert_eq!(ansi::strip("done\x1b[3"), "done");
+    assert_eq!(ansi::strip("done\x1b"), "done");
+}
+
+#[test]
+fn unknown_codes_keep_the_style() {
+    assert_eq!(
+        runs("\x1b[32;5;8;999mgreen"),
+        [("green".to_string(), Style::default().fg(Color::Green))]
+    );
+}
+
+#[test]
+fn drops_dcs_sos_pm_and_apc_strings() {
+    assert_eq!(ansi::sanitize("a\x1bP1$r0m\x1b\\b"), "ab");
+    assert_eq!(
+        ansi::sanitize("a\x1bXsos\x1b\\b\x1b^pm\x1b\\c\x1b_apc\x1b\\d"),
+        "abcd"
+    );
+    // BEL ends an OSC but not a DCS
+    assert_eq!(ansi::sanitize("a\x1bPq\x07#0;2\x1b\\b"), "ab");
+}
+
+#[test]
+fn drops_eight_bit_controls() {
+    assert_eq!(ansi::sanitize("\u{9b}1;31mred\u{9b}0m"), "red");
+    assert_eq!(ansi::sanitize("a\u{9d}0;title\u{9c}b"), "ab");
+    assert_eq!(ansi::sanitize("a\u{90}q\u{9c}b\u{85}c"), "abc");
+    // Started with ESC, ended with the 8-bit ST
+    assert_eq!(ansi::sanitize("a\x1b]0;title\u{9c}b"), "ab");
+    assert_eq!(
+        runs("\u{9b}32mgreen"),
+        [("green".to_string(), Style::default().fg(Color::Green))]
+    );
+}
+
+#[test]
+fn drops_bare_escapes() {
+    assert_eq!(ansi::sanitize("\x1b7saved\x1b8\x1bc"), "saved");
+    assert_eq!(ansi::sanitize("\x1b(Bascii\x1b#8"), "ascii");
+    // Non-ASCII text after ESC was never part of a sequence
+    assert_eq!(ansi::sanitize("\x1bé"), "é");
+}
+
+#[test]
+fn cancel_aborts_a_sequence() {
+    assert_eq!(ansi::sanitize("a\x1b[31\x18b"), "ab");
+    assert_eq!(ansi::sanitize("a\x1b]0;title\x1ab"), "ab");
+    assert_eq!(ansi::strip("a\x1b[31\x18m"), "am");
+}
+
+#[test]
+fn controls_inside_a_sequence_do_not_break_it() {
+    assert_eq!(
+        runs("\x1b[3\r1mred"),
+        [("red".to_string(), Style::default().fg(Color::Red))]
+    );
+    assert_eq!(ansi::sanitize("\x1b[3\r1mred\x7f"), "red");
+}
+
+#[test]
+fn sanitize_keeps_tabs_and_borrows_clean_lines() {
+    assert!(matches!(ansi::sanitize("a\tb ✓"), Cow::Borrowed("a\tb ✓")));
+    assert_eq!(ansi::sanitize("a\tb\r\n"), "a\tb");
+}
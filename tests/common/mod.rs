@@ -0,0 +1,352 @@
+//! A fake Docker daemon serving the Engine API endpoints dockerlogs uses
+//! over a temporary Unix socket, TCP or TLS, backed by a [`FakeBackend`].
+
+// Each test binary uses a different part of the harness
+#![allow(dead_code)]
+
+pub mod tls;
+
+use chrono::{DateTime, Utc};
+use docker_api::conn::TtyChunk;
+use dockerlogs::fake::{Dropped, FakeBackend, FakeContainer, Query};
+use dockerlogs::lines::Stream;
+use futures::StreamExt;
+use openssl::ssl::{Ssl, SslAcceptor};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, UnixListener};
+use tokio::task::JoinHandle;
+use tokio_openssl::SslStream;
+
+/// A running fake daemon. Its socket, if any, is removed when it is dropped.
+pub struct FakeDaemon {
+    pub backend: FakeBackend,
+    url: String,
+    socket: Option<PathBuf>,
+    server: JoinHandle<()>,
+}
+
+impl FakeDaemon {
+    /// Listens on a Unix socket.
+    pub async fn start(backend: FakeBackend) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let socket = std::env::temp_dir().join(format!(
+            "dockerlogs-test-{}-{}.sock",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        Self::start_at(backend, socket).await
+    }
+
+    /// Listens on a Unix socket at `socket`.
+    pub async fn start_at(backend: FakeBackend, socket: PathBuf) -> Self {
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).expect("cannot bind the fake daemon socket");
+
+        let served = backend.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(served.clone(), stream));
+            }
+        });
+        Self {
+            backend,
+            url: format!("unix://{}", socket.display()),
+            socket: Some(socket),
+            server,
+        }
+    }
+
+    /// Listens on a loopback TCP port, without TLS.
+    pub async fn start_tcp(backend: FakeBackend) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let served = backend.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(served.clone(), stream));
+            }
+        });
+        Self {
+            backend,
+            url,
+            socket: None,
+            server,
+        }
+    }
+
+    /// Listens on a loopback TCP port, speaking TLS through `acceptor`.
+    pub async fn start_tls(backend: FakeBackend, acceptor: SslAcceptor) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let served = backend.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let ssl = Ssl::new(acceptor.context()).unwrap();
+                let served = served.clone();
+                tokio::spawn(async move {
+                    let mut stream = SslStream::new(ssl, stream).unwrap();
+                    // Failed handshakes, such as untrusted clients, get no answer
+                    if Pin::new(&mut stream).accept().await.is_ok() {
+                        serve(served, stream).await;
+                    }
+                });
+            }
+        });
+        Self {
+            backend,
+            url,
+            socket: None,
+            server,
+        }
+    }
+
+    /// The value to pass to `--url`.
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub fn socket(&self) -> &Path {
+        self.socket
+            .as_deref()
+            .expect("the daemon does not listen on a Unix socket")
+    }
+}
+
+impl Drop for FakeDaemon {
+    fn drop(&mut self) {
+        self.server.abort();
+        if let Some(socket) = &self.socket {
+            let _ = std::fs::remove_file(socket);
+        }
+    }
+}
+
+/// Answers the single request of a connection.
+async fn serve(backend: FakeBackend, stream: impl AsyncRead + AsyncWrite + Unpin) {
+    let mut stream = BufReader::new(stream);
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await.is_err() {
+        return;
+    }
+    // Requests carry no body, skip the headers
+    loop {
+        let mut header = String::new();
+        match stream.read_line(&mut header).await {
+            Ok(0) | Err(_) => return,
+            Ok(_) if header == "\r\n" => break,
+            Ok(_) => {}
+        }
+    }
+    let mut stream = stream.into_inner();
+    // A daemon that is down closes connections without answering
+    if backend.is_down() {
+        return;
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (target, None),
+    };
+    let query = Query::parse(query);
+    let segments: Vec<&str> = path
+        .trim_start_matches('/')
+        .split('/')
+        // Versioned clients prefix every path with /v1.xx
+        .skip_while(|segment| segment.starts_with('v') && segment.contains('.'))
+        .collect();
+
+    let _ = match segments.as_slice() {
+        ["_ping"] => respond(&mut stream, "200 OK", "text/plain", b"OK").await,
+        ["containers", "json"] => respond_json(&mut stream, &backend.list_json(&query)).await,
+        ["containers", id, "json"] => match backend.inspect_json(id) {
+            Some(info) => respond_json(&mut stream, &info).await,
+            None => not_found(&mut stream, id).await,
+        },
+        ["containers", id, "logs"] => {
+            if !backend.contains(id) {
+                let _ = not_found(&mut stream, id).await;
+                return;
+            }
+            let frames = backend
+                .log_frames(id, query)
+                .map(|frame| frame.map(|frame| multiplexed(&frame)));
+            stream_chunks(
+                &mut stream,
+                "application/vnd.docker.multiplexed-stream",
+                frames,
+            )
+            .await
+        }
+        ["events"] => {
+            let events = backend.event_stream(&query).map(|event| {
+                event.map(|event| {
+                    let mut line = event.to_string().into_bytes();
+                    line.push(b'\n');
+                    line
+                })
+            });
+            stream_chunks(&mut stream, "application/json", events).await
+        }
+        _ => {
+            let body = serde_json::json!({ "message": format!("page not found: {}", path) });
+            respond(
+                &mut stream,
+                "404 Not Found",
+                "application/json",
+                body.to_string().as_bytes(),
+            )
+            .await
+        }
+    };
+}
+
+/// Frames a chunk the way Docker multiplexes the streams of a container
+/// without a TTY: stream number, three padding bytes, big-endian length.
+fn multiplexed(frame: &TtyChunk) -> Vec<u8> {
+    let stream = match frame {
+        TtyChunk::StdIn(_) => 0,
+        TtyChunk::StdOut(_) => 1,
+        TtyChunk::StdErr(_) => 2,
+    };
+    let mut bytes = vec![stream, 0, 0, 0];
+    bytes.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(frame);
+    bytes
+}
+
+async fn respond(
+    stream: &mut (impl AsyncWrite + Unpin),
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {}\r\nApi-Version: 1.43\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+    stream.shutdown().await
+}
+
+async fn respond_json(
+    stream: &mut (impl AsyncWrite + Unpin),
+    body: &serde_json::Value,
+) -> std::io::Result<()> {
+    respond(
+        stream,
+        "200 OK",
+        "application/json",
+        body.to_string().as_bytes(),
+    )
+    .await
+}
+
+async fn not_found(stream: &mut (impl AsyncWrite + Unpin), id: &str) -> std::io::Result<()> {
+    let body = serde_json::json!({ "message": format!("No such container: {}", id) });
+    respond(
+        stream,
+        "404 Not Found",
+        "application/json",
+        body.to_string().as_bytes(),
+    )
+    .await
+}
+
+/// Sends every item as its own HTTP chunk, as soon as it is available. A
+/// dropped stream closes the connection in the middle of the body.
+async fn stream_chunks(
+    stream: &mut (impl AsyncWrite + Unpin),
+    content_type: &str,
+    mut chunks: impl futures::Stream<Item = Result<Vec<u8>, Dropped>> + Unpin,
+) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nApi-Version: 1.43\r\nContent-Type: {}\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        content_type
+    );
+    stream.write_all(head.as_bytes()).await?;
+    while let Some(chunk) = chunks.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(Dropped) => return Ok(()),
+        };
+        stream
+            .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+            .await?;
+        stream.write_all(&chunk).await?;
+        stream.write_all(b"\r\n").await?;
+        stream.flush().await?;
+    }
+    stream.write_all(b"0\r\n\r\n").await?;
+    stream.shutdown().await
+}
+
+#[derive(Deserialize)]
+struct Fixture {
+    containers: Vec<FixtureContainer>,
+}
+
+#[derive(Deserialize)]
+struct FixtureContainer {
+    id: String,
+    name: String,
+    image: String,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default = "running")]
+    running: bool,
+    #[serde(default)]
+    exit_code: isize,
+    #[serde(default)]
+    logs: Vec<FixtureLine>,
+}
+
+fn running() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct FixtureLine {
+    at: DateTime<Utc>,
+    stream: String,
+    message: String,
+}
+
+/// Loads `tests/fixtures/<name>.json` into a fresh backend.
+pub fn load_fixture(name: &str) -> FakeBackend {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.json", name));
+    let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("cannot read {}: {}", path.display(), err));
+    let fixture: Fixture = serde_json::from_str(&text)
+        .unwrap_or_else(|err| panic!("invalid fixture {}: {}", path.display(), err));
+
+    let backend = FakeBackend::new();
+    for container in fixture.containers {
+        let mut fake = FakeContainer::new(&container.id, &container.name).image(&container.image);
+        for (key, value) in &container.labels {
+            fake = fake.label(key, value);
+        }
+        for line in &container.logs {
+            let stream = match line.stream.as_str() {
+                "stderr" => Stream::StdErr,
+                _ => Stream::StdOut,
+            };
+            fake = fake.line_at(stream, line.at, &line.message);
+        }
+        if !container.running {
+            fake = fake.exited(container.exit_code);
+        }
+        backend.add(fake);
+    }
+    backend
+}
@@ -0,0 +1,155 @@
+//! Throwaway certificates for a fake daemon speaking TLS: a CA, a server
+//! certificate for `localhost` and `127.0.0.1`, and a client certificate.
+
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{SslAcceptor, SslMethod, SslVerifyMode};
+use openssl::x509::extension::{
+    BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+};
+use openssl::x509::{X509Builder, X509NameBuilder, X509};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct Certificates {
+    dir: PathBuf,
+    ca: X509,
+    server_cert: X509,
+    server_key: PKey<Private>,
+}
+
+impl Certificates {
+    /// Generates a fresh set, the client side stored in a directory laid
+    /// out like `DOCKER_CERT_PATH`.
+    pub fn generate() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "dockerlogs-certs-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ca_key = key();
+        let ca = {
+            let mut builder = builder("dockerlogs test CA", &ca_key, None);
+            builder
+                .append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                .unwrap();
+            builder
+                .append_extension(
+                    KeyUsage::new()
+                        .critical()
+                        .key_cert_sign()
+                        .crl_sign()
+                        .build()
+                        .unwrap(),
+                )
+                .unwrap();
+            builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+            builder.build()
+        };
+
+        let server_key = key();
+        let server_cert = {
+            let mut builder = builder("localhost", &server_key, Some(&ca));
+            let names = SubjectAlternativeName::new()
+                .dns("localhost")
+                .ip("127.0.0.1")
+                .build(&builder.x509v3_context(Some(&ca), None))
+                .unwrap();
+            builder.append_extension(names).unwrap();
+            builder
+                .append_extension(ExtendedKeyUsage::new().server_auth().build().unwrap())
+                .unwrap();
+            builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+            builder.build()
+        };
+
+        let client_key = key();
+        let client_cert = {
+            let mut builder = builder("client", &client_key, Some(&ca));
+            builder
+                .append_extension(ExtendedKeyUsage::new().client_auth().build().unwrap())
+                .unwrap();
+            builder.sign(&ca_key, MessageDigest::sha256()).unwrap();
+            builder.build()
+        };
+
+        std::fs::write(dir.join("ca.pem"), ca.to_pem().unwrap()).unwrap();
+        std::fs::write(dir.join("cert.pem"), client_cert.to_pem().unwrap()).unwrap();
+        std::fs::write(
+            dir.join("key.pem"),
+            client_key.private_key_to_pem_pkcs8().unwrap(),
+        )
+        .unwrap();
+        Self {
+            dir,
+            ca,
+            server_cert,
+            server_key,
+        }
+    }
+
+    /// Holds `ca.pem`, and the client's `cert.pem` and `key.pem`.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Server side of the handshake, turning away clients without a
+    /// certificate signed by the CA.
+    pub fn acceptor(&self) -> SslAcceptor {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_private_key(&self.server_key).unwrap();
+        acceptor.set_certificate(&self.server_cert).unwrap();
+        acceptor.cert_store_mut().add_cert(self.ca.clone()).unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        // Needed to resume sessions, which clients do on every new request
+        acceptor.set_session_id_context(b"dockerlogs").unwrap();
+        acceptor.build()
+    }
+}
+
+impl Drop for Certificates {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn key() -> PKey<Private> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+/// A certificate for `name` valid for a day, issued by `issuer` or self-signed.
+fn builder(name: &str, key: &PKey<Private>, issuer: Option<&X509>) -> X509Builder {
+    static SERIAL: AtomicUsize = AtomicUsize::new(1);
+    let mut subject = X509NameBuilder::new().unwrap();
+    subject.append_entry_by_text("CN", name).unwrap();
+    let subject = subject.build();
+
+    let mut builder = X509Builder::new().unwrap();
+    builder.set_version(2).unwrap();
+    let serial = BigNum::from_u32(SERIAL.fetch_add(1, Ordering::Relaxed) as u32).unwrap();
+    builder
+        .set_serial_number(&serial.to_asn1_integer().unwrap())
+        .unwrap();
+    builder.set_subject_name(&subject).unwrap();
+    match issuer {
+        Some(issuer) => builder.set_issuer_name(issuer.subject_name()).unwrap(),
+        None => builder.set_issuer_name(&subject).unwrap(),
+    }
+    builder.set_pubkey(key).unwrap();
+    builder
+        .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    builder
+        .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    builder
+}
@@ -0,0 +1,691 @@
+//! Runs the dockerlogs binary against a fake daemon serving fixtures.
+
+mod common;
+
+use chrono::TimeZone;
+use common::tls::Certificates;
+use common::{load_fixture, FakeDaemon};
+use dockerlogs::fake::{FakeBackend, FakeContainer};
+use dockerlogs::lines::Stream;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The binary, unaffected by the Docker settings of whoever runs the tests.
+fn command() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dockerlogs"));
+    command
+        .env("CLICOLOR", "0")
+        .env("NO_COLOR", "1")
+        .env("DOCKER_CONFIG", "/nonexistent")
+        .env_remove("DOCKER_HOST")
+        .env_remove("DOCKER_CONTEXT")
+        .env_remove("DOCKER_TLS_VERIFY")
+        .env_remove("DOCKER_CERT_PATH");
+    command
+}
+
+fn dockerlogs(url: &str) -> Command {
+    let mut command = command();
+    command.arg("--url").arg(url);
+    command
+}
+
+async fn run(daemon: &FakeDaemon, args: &[&str]) -> Output {
+    let mut command = dockerlogs(&daemon.url());
+    command.args(args);
+    succeed(command).await
+}
+
+/// Runs the binary to completion. The daemon lives on the test runtime, so
+/// waiting happens on a blocking thread.
+async fn succeed(mut command: Command) -> Output {
+    let output = tokio::time::timeout(
+        TIMEOUT,
+        tokio::task::spawn_blocking(move || command.output()),
+    )
+    .await
+    .expect("dockerlogs did not exit")
+    .unwrap()
+    .expect("cannot run dockerlogs");
+    assert!(
+        output.status.success(),
+        "dockerlogs failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn stdout_lines(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// The log lines only, without the lifecycle notices.
+fn log_lines(output: &Output) -> Vec<String> {
+    stdout_lines(output)
+        .into_iter()
+        .filter(|line| !line.starts_with(">>>"))
+        .collect()
+}
+
+fn lines_of(lines: &[String], name: &str) -> Vec<String> {
+    let prefix = format!("{} ", name);
+    lines
+        .iter()
+        .filter(|line| line.starts_with(&prefix))
+        .cloned()
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prints_the_logs_of_running_containers() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let output = run(&daemon, &[]).await;
+    let stdout = stdout_lines(&output);
+
+    assert!(stdout.contains(&">>> ✓ Started watching container shop-api".to_string()));
+    assert_eq!(
+        lines_of(&stdout, "shop-api"),
+        [
+            "shop-api o: listening on :8080",
+            "shop-api o: GET /orders 200",
+            "shop-api e: ERROR payment gateway timeout",
+            "shop-api o: GET /health 200",
+        ]
+    );
+    assert_eq!(
+        lines_of(&stdout, "shop-db"),
+        [
+            "shop-db o: database system is ready",
+            "shop-db e: ERROR deadlock detected",
+        ]
+    );
+    assert!(lines_of(&stdout, "migrate").is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn includes_stopped_containers_with_all() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let output = run(&daemon, &["--all", "--container-regex", "^migrate$"]).await;
+    assert_eq!(log_lines(&output), ["migrate e: migration 42 failed"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merges_containers_chronologically_with_sort() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let output = run(&daemon, &["--sort", "--timestamps"]).await;
+    assert_eq!(
+        log_lines(&output),
+        [
+            "shop-api o: 2024-05-01T12:00:00.100Z listening on :8080",
+            "shop-db o: 2024-05-01T12:00:01.200Z database system is ready",
+            "shop-api o: 2024-05-01T12:00:02.300Z GET /orders 200",
+            "shop-api e: 2024-05-01T12:00:04.500Z ERROR payment gateway timeout",
+            "shop-db e: 2024-05-01T12:00:05.600Z ERROR deadlock detected",
+            "shop-api o: 2024-05-01T12:00:06.700Z GET /health 200",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn greps_across_containers() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let output = run(&daemon, &["--sort", "--grep", "ERROR"]).await;
+    assert_eq!(
+        log_lines(&output),
+        [
+            "shop-api e: ERROR payment gateway timeout",
+            "shop-db e: ERROR deadlock detected",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn selects_containers_and_streams() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let output = run(&daemon, &["--container-regex", "db$", "--no-stdout"]).await;
+    assert_eq!(log_lines(&output), ["shop-db e: ERROR deadlock detected"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prints_json_lines() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let output = run(
+        &daemon,
+        &["--output", "json", "--container-regex", "db$", "-l", "1"],
+    )
+    .await;
+    let values: Vec<serde_json::Value> = stdout_lines(&output)
+        .iter()
+        .map(|line| serde_json::from_str(line).expect("every line is JSON"))
+        .collect();
+    let types: Vec<&str> = values
+        .iter()
+        .map(|value| value["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, ["started", "log"]);
+    assert_eq!(values[1]["name"], "shop-db");
+    assert_eq!(values[1]["image"], "postgres:16");
+    assert_eq!(values[1]["stream"], "stderr");
+    assert_eq!(values[1]["timestamp"], "2024-05-01T12:00:05.600Z");
+    assert_eq!(values[1]["message"], "ERROR deadlock detected");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sanitizes_escape_sequences() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    daemon.backend.log(
+        "d2d2d2d2d2d2d2d2",
+        Stream::StdOut,
+        "\x1b[1mvacuum\x1b[0m done\x1b]52;c;cHduZWQ=\x07\u{9b}2J\x1bP+q544e\x1b\\",
+    );
+    let output = run(&daemon, &["--sanitize", "--container-regex", "db$"]).await;
+    assert_eq!(
+        log_lines(&output),
+        [
+            "shop-db o: database system is ready",
+            "shop-db e: ERROR deadlock detected",
+            "shop-db o: vacuum done",
+        ]
+    );
+}
+
+/// A `dockerlogs --follow` run, killed when dropped.
+struct Follower {
+    child: Child,
+    lines: mpsc::Receiver<String>,
+}
+
+impl Follower {
+    fn spawn(daemon: &FakeDaemon) -> Self {
+        let mut child = dockerlogs(&daemon.url())
+            .arg("--follow")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("cannot run dockerlogs");
+
+        // Read stdout on its own thread so the test can time out
+        let stdout = child.stdout.take().unwrap();
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { child, lines }
+    }
+
+    /// Reads output until every `wanted` line is printed, returning all
+    /// the lines read.
+    fn wait_for(&self, wanted: &[&str]) -> Vec<String> {
+        let mut seen = Vec::new();
+        while !wanted
+            .iter()
+            .all(|wanted| seen.iter().any(|line| line == wanted))
+        {
+            let line = tokio::task::block_in_place(|| self.lines.recv_timeout(TIMEOUT))
+                .unwrap_or_else(|_| panic!("timed out waiting for {:?} after {:?}", wanted, seen));
+            seen.push(line);
+        }
+        seen
+    }
+}
+
+impl Drop for Follower {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn follows_containers_started_later() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let follower = Follower::spawn(&daemon);
+
+    tokio::time::timeout(TIMEOUT, daemon.backend.subscribed())
+        .await
+        .expect("dockerlogs never subscribed to events");
+    daemon
+        .backend
+        .start(FakeContainer::new("w4w4w4w4w4w4w4w4", "worker"));
+    // The default tail also covers a line logged before the watcher attaches
+    daemon
+        .backend
+        .log("w4w4w4w4w4w4w4w4", Stream::StdOut, "job 7 done");
+
+    let seen = follower.wait_for(&["worker o: job 7 done"]);
+    assert!(seen.contains(&">>> ✓ Started watching container worker".to_string()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reconnects_after_a_daemon_restart() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let follower = Follower::spawn(&daemon);
+    follower.wait_for(&["shop-db e: ERROR deadlock detected"]);
+    tokio::time::timeout(TIMEOUT, daemon.backend.subscribed())
+        .await
+        .expect("dockerlogs never subscribed to events");
+
+    daemon.backend.disconnect();
+    follower.wait_for(&[">>> ✗ Docker daemon disconnected, reconnecting..."]);
+    daemon
+        .backend
+        .log("d2d2d2d2d2d2d2d2", Stream::StdOut, "checkpoint complete");
+    daemon.backend.restore();
+
+    let seen = follower.wait_for(&[
+        ">>> ✓ Docker daemon reconnected",
+        "shop-db o: checkpoint complete",
+    ]);
+    // Nothing printed before the restart comes again
+    assert!(
+        !seen.iter().any(|line| line.contains("deadlock")),
+        "{:?}",
+        seen
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fails_with_the_connection_exit_code_without_a_daemon() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let url = daemon.url();
+    // The socket goes away with the daemon
+    drop(daemon);
+
+    let mut command = dockerlogs(&url);
+    let output = tokio::task::spawn_blocking(move || command.output())
+        .await
+        .unwrap()
+        .expect("cannot run dockerlogs");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error:"));
+}
+
+/// A second daemon, with one worker container.
+async fn worker_daemon() -> FakeDaemon {
+    let backend = FakeBackend::new();
+    backend.add(FakeContainer::new("w1w1w1w1w1w1w1w1", "worker").line(Stream::StdOut, "job done"));
+    FakeDaemon::start(backend).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tails_several_daemons_under_their_aliases() {
+    let shop = FakeDaemon::start(load_fixture("shop")).await;
+    let jobs = worker_daemon().await;
+
+    let mut command = command();
+    command
+        .arg("--url")
+        .arg(format!("shop={}", shop.url()))
+        .arg("--url")
+        .arg(format!("jobs={}", jobs.url()));
+    let stdout = stdout_lines(&succeed(command).await);
+
+    assert!(stdout.contains(&">>> ✓ Started watching container jobs/worker".to_string()));
+    assert_eq!(
+        lines_of(&stdout, "jobs/worker"),
+        ["jobs/worker o: job done"]
+    );
+    assert_eq!(
+        lines_of(&stdout, "shop/shop-db"),
+        [
+            "shop/shop-db o: database system is ready",
+            "shop/shop-db e: ERROR deadlock detected",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keeps_tailing_the_other_daemons_when_one_is_unreachable() {
+    let jobs = worker_daemon().await;
+    let config = DockerConfig::new();
+    let hosts = config.dir.join("hosts");
+    let derived = jobs
+        .socket()
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+    std::fs::write(
+        &hosts,
+        format!(
+            "# Daemons to tail\n\n{}\n  gone={}\n",
+            jobs.url(),
+            UNREACHABLE
+        ),
+    )
+    .unwrap();
+
+    let mut command = config.command();
+    command.arg("--hosts-file").arg(&hosts);
+    let output = tokio::task::spawn_blocking(move || command.output())
+        .await
+        .unwrap()
+        .expect("cannot run dockerlogs");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error:"));
+    // The daemon without an alias is named after its socket
+    assert_eq!(
+        log_lines(&output),
+        [format!("{}/worker o: job done", derived)]
+    );
+}
+
+/// A throwaway docker CLI configuration directory, for `DOCKER_CONFIG`.
+struct DockerConfig {
+    dir: PathBuf,
+}
+
+impl DockerConfig {
+    fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "dockerlogs-config-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    /// Stores a context the way `docker context create` does. The docker
+    /// CLI names the metadata directory after the SHA-256 of the context
+    /// name, dockerlogs scans them so any name does.
+    fn context(&self, name: &str, host: &str) -> &Self {
+        let meta = self.dir.join("contexts/meta").join(name);
+        std::fs::create_dir_all(&meta).unwrap();
+        let content = serde_json::json!({
+            "Name": name,
+            "Metadata": {},
+            "Endpoints": { "docker": { "Host": host, "SkipTLSVerify": false } },
+        });
+        std::fs::write(meta.join("meta.json"), content.to_string()).unwrap();
+        self
+    }
+
+    /// Stores the client certificates of a context where `docker context
+    /// create --docker "...,cert=...,key=...,ca=..."` puts them.
+    fn tls_files(&self, name: &str, certs: &Certificates) -> &Self {
+        let dir = self.dir.join("contexts/tls").join(name).join("docker");
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["ca.pem", "cert.pem", "key.pem"] {
+            std::fs::copy(certs.dir().join(file), dir.join(file)).unwrap();
+        }
+        self
+    }
+
+    /// Makes `name` the context `docker context use` last switched to.
+    fn current(&self, name: &str) -> &Self {
+        let content = serde_json::json!({ "currentContext": name });
+        std::fs::write(self.dir.join("config.json"), content.to_string()).unwrap();
+        self
+    }
+
+    /// The binary reading this configuration, without `--url`.
+    fn command(&self) -> Command {
+        let mut command = command();
+        command.env("DOCKER_CONFIG", &self.dir);
+        command
+    }
+}
+
+impl Drop for DockerConfig {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+const UNREACHABLE: &str = "unix:///nonexistent/docker.sock";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connects_to_the_context_given_with_context() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let config = DockerConfig::new();
+    config
+        .context("fake", &daemon.url())
+        .context("elsewhere", UNREACHABLE)
+        .current("elsewhere");
+
+    let mut command = config.command();
+    // The flag wins over the environment and the current context
+    command
+        .args(["--context", "fake", "--container-regex", "^shop-db$"])
+        .env("DOCKER_HOST", UNREACHABLE);
+    let output = succeed(command).await;
+    assert_eq!(
+        log_lines(&output),
+        [
+            "shop-db o: database system is ready",
+            "shop-db e: ERROR deadlock detected",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prefers_docker_host_to_the_current_context() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let config = DockerConfig::new();
+    config
+        .context("elsewhere", UNREACHABLE)
+        .current("elsewhere");
+
+    let mut command = config.command();
+    command
+        .args(["--container-regex", "^shop-db$"])
+        .env("DOCKER_HOST", daemon.url());
+    let output = succeed(command).await;
+    assert_eq!(log_lines(&output).len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn uses_docker_context_then_the_current_context() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let config = DockerConfig::new();
+    config
+        .context("fake", &daemon.url())
+        .context("elsewhere", UNREACHABLE);
+
+    config.current("fake");
+    let mut command = config.command();
+    command.args(["--container-regex", "^shop-db$"]);
+    assert_eq!(log_lines(&succeed(command).await).len(), 2);
+
+    config.current("elsewhere");
+    let mut command = config.command();
+    command
+        .args(["--container-regex", "^shop-db$"])
+        .env("DOCKER_CONTEXT", "fake");
+    assert_eq!(log_lines(&succeed(command).await).len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn fails_with_the_config_exit_code_for_an_unknown_context() {
+    let config = DockerConfig::new();
+    let mut command = config.command();
+    command.args(["--context", "missing"]);
+    let output = tokio::task::spawn_blocking(move || command.output())
+        .await
+        .unwrap()
+        .expect("cannot run dockerlogs");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("context 'missing' not found"));
+}
+
+/// Tails the one database container of the shop fixture.
+fn expect_shop_db(output: &Output) {
+    assert_eq!(
+        log_lines(output),
+        [
+            "shop-db o: database system is ready",
+            "shop-db e: ERROR deadlock detected",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connects_over_tls_with_separately_named_files() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tls(load_fixture("shop"), certs.acceptor()).await;
+
+    // Named unlike the docker CLI layout, so they have to be staged
+    let separate = certs.dir().join("separate");
+    std::fs::create_dir(&separate).unwrap();
+    let mut command = dockerlogs(&daemon.url());
+    for (flag, file, name) in [
+        ("--tls-cert", "cert.pem", "client.crt"),
+        ("--tls-key", "key.pem", "client.key"),
+        ("--tls-ca", "ca.pem", "root.crt"),
+    ] {
+        std::fs::copy(certs.dir().join(file), separate.join(name)).unwrap();
+        command.arg(flag).arg(separate.join(name));
+    }
+    command.args(["--container-regex", "^shop-db$"]);
+    expect_shop_db(&succeed(command).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connects_over_tls_with_docker_tls_verify() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tls(load_fixture("shop"), certs.acceptor()).await;
+
+    let mut command = command();
+    command
+        .args(["--container-regex", "^shop-db$"])
+        .env("DOCKER_HOST", daemon.url())
+        .env("DOCKER_TLS_VERIFY", "1")
+        .env("DOCKER_CERT_PATH", certs.dir());
+    expect_shop_db(&succeed(command).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connects_over_tls_with_a_context() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tls(load_fixture("shop"), certs.acceptor()).await;
+    let config = DockerConfig::new();
+    config
+        .context("secure", &daemon.url())
+        .tls_files("secure", &certs);
+
+    let mut command = config.command();
+    command.args(["--context", "secure", "--container-regex", "^shop-db$"]);
+    expect_shop_db(&succeed(command).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refuses_a_daemon_signed_by_another_ca() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tls(load_fixture("shop"), certs.acceptor()).await;
+    let strangers = Certificates::generate();
+
+    let mut command = command();
+    command
+        .env("DOCKER_HOST", daemon.url())
+        .env("DOCKER_TLS_VERIFY", "1")
+        .env("DOCKER_CERT_PATH", strangers.dir());
+    let output = tokio::task::spawn_blocking(move || command.output())
+        .await
+        .unwrap()
+        .expect("cannot run dockerlogs");
+    assert!(!output.status.success());
+    assert!(log_lines(&output).is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn docker_cert_path_alone_does_not_enable_tls() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tcp(load_fixture("shop")).await;
+
+    let mut command = command();
+    command
+        .args(["--container-regex", "^shop-db$"])
+        .env("DOCKER_HOST", daemon.url())
+        .env("DOCKER_CERT_PATH", certs.dir());
+    expect_shop_db(&succeed(command).await);
+}
+
+/// `--tls` with only the client certificate and key in `DOCKER_CERT_PATH`.
+fn tls_without_ca(daemon: &FakeDaemon, certs: &Certificates) -> Command {
+    let client = certs.dir().join("client");
+    std::fs::create_dir(&client).unwrap();
+    for file in ["cert.pem", "key.pem"] {
+        std::fs::copy(certs.dir().join(file), client.join(file)).unwrap();
+    }
+    let mut command = command();
+    command
+        .arg("--tls")
+        .env("DOCKER_HOST", daemon.url())
+        .env("DOCKER_CERT_PATH", client)
+        .env_remove("SSL_CERT_FILE")
+        .env_remove("SSL_CERT_DIR");
+    command
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn verifies_the_daemon_against_the_system_cas_without_a_ca_file() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tls(load_fixture("shop"), certs.acceptor()).await;
+
+    // Stands in for the system bundle
+    let mut command = tls_without_ca(&daemon, &certs);
+    command
+        .args(["--container-regex", "^shop-db$"])
+        .env("SSL_CERT_FILE", certs.dir().join("ca.pem"));
+    expect_shop_db(&succeed(command).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn refuses_a_daemon_the_system_cas_do_not_vouch_for() {
+    let certs = Certificates::generate();
+    let daemon = FakeDaemon::start_tls(load_fixture("shop"), certs.acceptor()).await;
+
+    let mut command = tls_without_ca(&daemon, &certs);
+    let output = tokio::task::spawn_blocking(move || command.output())
+        .await
+        .unwrap()
+        .expect("cannot run dockerlogs");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("certificate verify failed"));
+    assert!(log_lines(&output).is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reads_the_whole_time_window_unless_told_otherwise() {
+    let start = chrono::Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    let mut container = FakeContainer::new("b5b5b5b5b5b5b5b5", "batch");
+    for i in 0..40 {
+        container = container.line_at(
+            Stream::StdOut,
+            start + chrono::Duration::seconds(i),
+            &format!("row {}", i),
+        );
+    }
+    let backend = FakeBackend::new();
+    backend.add(container);
+    let daemon = FakeDaemon::start(backend).await;
+
+    // More lines than the default tail of 20
+    let output = run(&daemon, &["--since", "2024-05-01T12:00:05Z"]).await;
+    let lines = log_lines(&output);
+    assert_eq!(lines.len(), 35);
+    assert_eq!(lines[0], "batch o: row 5");
+
+    let output = run(&daemon, &["--since", "2024-05-01T12:00:05Z", "-l", "1"]).await;
+    assert_eq!(log_lines(&output), ["batch o: row 39"]);
+
+    // Without a window the default tail still applies
+    let output = run(&daemon, &[]).await;
+    assert_eq!(log_lines(&output).len(), 20);
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use common::FakeDaemon;
+use dockerlogs::endpoint::{self, ConnectionArgs, Endpoint};
+use dockerlogs::error;
+use dockerlogs::fake::FakeBackend;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn args(urls: &[&str], hosts_file: Option<PathBuf>) -> ConnectionArgs {
+    ConnectionArgs {
+        url: urls.iter().map(|url| url.to_string()).collect(),
+        hosts_file,
+        context: None,
+        tls: false,
+        tls_cert: None,
+        tls_key: None,
+        tls_ca: None,
+    }
+}
+
+/// The alias and URL of every resolved endpoint.
+fn resolved(args: &ConnectionArgs) -> Vec<(String, String)> {
+    Endpoint::resolve_all(args)
+        .unwrap()
+        .into_iter()
+        .map(|endpoint| (endpoint.alias.unwrap_or_default(), endpoint.url))
+        .collect()
+}
+
+/// A fresh temporary directory, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "dockerlogs-endpoint-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    /// A path within this directory, with its parent directories created.
+    fn path(&self, path: &str) -> PathBuf {
+        let path = self.0.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn a_single_url_gets_no_alias() {
+    let args = args(&["unix:///run/user/1000/docker.sock"], None);
+    assert_eq!(
+        resolved(&args),
+        [(
+            String::new(),
+            "unix:///run/user/1000/docker.sock".to_string()
+        )]
+    );
+}
+
+#[test]
+fn names_daemons_after_their_socket_or_host() {
+    let args = args(
+        &[
+            "prod=unix:///var/run/docker.sock",
+            "unix:///run/user/1000/podman/podman.sock",
+            "ssh://deploy@build.example.com",
+        ],
+        None,
+    );
+    let aliases: Vec<String> = resolved(&args)
+        .into_iter()
+        .map(|(alias, _)| alias)
+        .collect();
+    assert_eq!(aliases, ["prod", "podman", "deploy@build.example.com"]);
+}
+
+#[test]
+fn falls_back_to_the_position_when_a_name_is_taken() {
+    let args = args(
+        &[
+            "unix:///run/a/docker.sock",
+            "unix:///run/b/docker.sock",
+            "prod=unix:///run/prod-1.sock",
+            "unix:///srv/prod.sock",
+        ],
+        None,
+    );
+    let aliases: Vec<String> = resolved(&args)
+        .into_iter()
+        .map(|(alias, _)| alias)
+        .collect();
+    assert_eq!(aliases, ["docker", "host2", "prod", "host4"]);
+}
+
+#[test]
+fn reads_hosts_files_after_the_urls() {
+    let dir = TempDir::new();
+    let hosts = dir.path("hosts");
+    std::fs::write(
+        &hosts,
+        "# Build farm\n\
+         \n\
+         build-1=unix:///run/build-1.sock\n\
+         \x20  # indented comment\n\
+         \x20 unix:///run/build-2.sock  \n",
+    )
+    .unwrap();
+    let args = args(&["local=unix:///var/run/docker.sock"], Some(hosts));
+    assert_eq!(
+        resolved(&args),
+        [
+            (
+                "local".to_string(),
+                "unix:///var/run/docker.sock".to_string()
+            ),
+            (
+                "build-1".to_string(),
+                "unix:///run/build-1.sock".to_string()
+            ),
+            (
+                "build-2".to_string(),
+                "unix:///run/build-2.sock".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fails_with_the_config_exit_code_for_a_missing_hosts_file() {
+    let args = args(&[], Some(PathBuf::from("/nonexistent/hosts")));
+    let err = Endpoint::resolve_all(&args).unwrap_err();
+    assert_eq!(err.exit_code(), 2);
+    assert!(err.to_string().contains("/nonexistent/hosts"));
+}
+
+#[test]
+fn rejects_aliases_outside_letters_digits_dashes_and_dots() {
+    for alias in ["", "my host", "a/b", "prod:1", "ünï"] {
+        let spec = format!("{}=unix:///var/run/docker.sock", alias);
+        let err = Endpoint::resolve_all(&args(&[&spec], None)).unwrap_err();
+        assert_eq!(err.exit_code(), 2, "{:?}", alias);
+        assert!(err.to_string().contains("invalid host alias"), "{}", err);
+    }
+    let args = args(&["eu-west.1=unix:///var/run/docker.sock"], None);
+    assert_eq!(resolved(&args)[0].0, "eu-west.1");
+}
+
+/// What dockerlogs falls back to when nothing was configured.
+fn unconfigured() -> Endpoint {
+    Endpoint {
+        alias: None,
+        url: "unix:///var/run/docker.sock".to_string(),
+        tls: None,
+        probe: true,
+    }
+}
+
+/// The candidates under `dir`, without the system-wide sockets of whoever
+/// runs the tests.
+fn candidates_in(dir: &TempDir) -> Vec<PathBuf> {
+    let runtime_dir = dir.0.join("run");
+    let home = dir.0.join("home");
+    endpoint::socket_candidates(Some(&runtime_dir), Some(&home))
+        .into_iter()
+        .filter(|path| path.starts_with(&dir.0))
+        .collect()
+}
+
+/// A socket file left behind by a daemon that is gone.
+fn stale_socket(path: &Path) {
+    drop(std::os::unix::net::UnixListener::bind(path).unwrap());
+}
+
+#[test]
+fn looks_for_rootless_docker_then_podman_then_desktop_vms() {
+    let candidates = endpoint::socket_candidates(
+        Some(Path::new("/run/user/1000")),
+        Some(Path::new("/home/me")),
+    );
+    let candidates: Vec<&str> = candidates.iter().map(|p| p.to_str().unwrap()).collect();
+    assert_eq!(
+        candidates,
+        [
+            "/var/run/docker.sock",
+            "/run/user/1000/docker.sock",
+            "/run/user/1000/podman/podman.sock",
+            "/run/podman/podman.sock",
+            "/home/me/.colima/default/docker.sock",
+            "/home/me/.colima/docker.sock",
+            "/home/me/.docker/run/docker.sock",
+            "/home/me/.rd/docker.sock",
+            "/home/me/.local/share/containers/podman/machine/podman.sock",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn uses_the_first_socket_that_answers() {
+    let dir = TempDir::new();
+    let stale = dir.path("run/docker.sock");
+    stale_socket(&stale);
+    let podman = FakeDaemon::start_at(FakeBackend::new(), dir.path("run/podman/podman.sock")).await;
+    let _colima =
+        FakeDaemon::start_at(FakeBackend::new(), dir.path("home/.colima/docker.sock")).await;
+
+    error::set_verbose(true);
+    error::capture_warnings();
+    let endpoint = unconfigured().discover_among(&candidates_in(&dir)).await;
+    assert_eq!(endpoint.url, podman.url());
+
+    // --verbose tells which sockets were tried and which one is used
+    let notes: Vec<String> = error::take_warnings()
+        .into_iter()
+        .filter(|note| note.contains(&dir.0.display().to_string()))
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            format!("no daemon answered at unix://{}", stale.display()),
+            format!("using the daemon at {}", podman.url()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_the_default_socket_when_none_answers() {
+    let dir = TempDir::new();
+    stale_socket(&dir.path("run/docker.sock"));
+
+    let endpoint = unconfigured().discover_among(&candidates_in(&dir)).await;
+    assert_eq!(endpoint.url, "unix:///var/run/docker.sock");
+}
+
+#[tokio::test]
+async fn leaves_configured_endpoints_alone() {
+    let dir = TempDir::new();
+    let _daemon = FakeDaemon::start_at(FakeBackend::new(), dir.path("run/docker.sock")).await;
+
+    let endpoint = Endpoint {
+        probe: false,
+        ..unconfigured()
+    };
+    let endpoint = endpoint.discover_among(&candidates_in(&dir)).await;
+    assert_eq!(endpoint.url, "unix:///var/run/docker.sock");
+}
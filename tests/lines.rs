@@ -0,0 +1,166 @@
+use chrono::{TimeZone, Utc};
+use docker_api::conn::TtyChunk;
+use dockerlogs::lines::{LineAssembler, LogLine, Stream};
+
+fn stdout(data: &str) -> TtyChunk {
+    TtyChunk::StdOut(data.as_bytes().to_vec())
+}
+
+fn stderr(data: &str) -> TtyChunk {
+    TtyChunk::StdErr(data.as_bytes().to_vec())
+}
+
+fn line(stream: Stream, message: &str) -> LogLine {
+    LogLine {
+        stream,
+        timestamp: None,
+        bytes: message.as_bytes().to_vec(),
+    }
+}
+
+/// Feeds every chunk and returns the lines completed by each one.
+fn push_all(assembler: &mut LineAssembler, chunks: Vec<TtyChunk>) -> Vec<Vec<LogLine>> {
+    chunks
+        .into_iter()
+        .map(|chunk| assembler.push(chunk))
+        .collect()
+}
+
+#[test]
+fn joins_a_line_split_across_chunks() {
+    let mut assembler = LineAssembler::new(false);
+    let completed = push_all(
+        &mut assembler,
+        vec![stdout("GET /ord"), stdout("ers 2"), stdout("00\n")],
+    );
+    assert_eq!(
+        completed,
+        [
+            vec![],
+            vec![],
+            vec![line(Stream::StdOut, "GET /orders 200")]
+        ]
+    );
+    assert!(assembler.finish().is_empty());
+}
+
+#[test]
+fn splits_several_lines_in_one_chunk() {
+    let mut assembler = LineAssembler::new(false);
+    assert_eq!(
+        assembler.push(stdout("one\ntwo\n\nthree\nfou")),
+        [
+            line(Stream::StdOut, "one"),
+            line(Stream::StdOut, "two"),
+            line(Stream::StdOut, ""),
+            line(Stream::StdOut, "three"),
+        ]
+    );
+    assert_eq!(
+        assembler.push(stdout("r\n")),
+        [line(Stream::StdOut, "four")]
+    );
+}
+
+#[test]
+fn drops_carriage_returns_before_newlines() {
+    let mut assembler = LineAssembler::new(false);
+    let completed = push_all(
+        &mut assembler,
+        vec![stdout("windows\r\nsplit\r"), stdout("\nbare\rreturn\n")],
+    );
+    assert_eq!(
+        completed,
+        [
+            vec![line(Stream::StdOut, "windows")],
+            vec![
+                line(Stream::StdOut, "split"),
+                line(Stream::StdOut, "bare\rreturn")
+            ],
+        ]
+    );
+}
+
+#[test]
+fn buffers_stdout_and_stderr_separately() {
+    let mut assembler = LineAssembler::new(false);
+    let completed = push_all(
+        &mut assembler,
+        vec![
+            stdout("out "),
+            stderr("err "),
+            stdout("done\n"),
+            stderr("done\n"),
+        ],
+    );
+    assert_eq!(
+        completed,
+        [
+            vec![],
+            vec![],
+            vec![line(Stream::StdOut, "out done")],
+            vec![line(Stream::StdErr, "err done")],
+        ]
+    );
+}
+
+#[test]
+fn finish_flushes_trailing_partial_lines() {
+    let mut assembler = LineAssembler::new(false);
+    assert!(assembler.push(stderr("no newline")).is_empty());
+    assert!(assembler.push(stdout("half\r")).is_empty());
+    assert_eq!(
+        assembler.finish(),
+        [
+            line(Stream::StdOut, "half"),
+            line(Stream::StdErr, "no newline"),
+        ]
+    );
+    // Nothing is left for a second flush
+    assert!(assembler.finish().is_empty());
+}
+
+#[test]
+fn parses_the_timestamp_of_split_lines() {
+    let mut assembler = LineAssembler::new(true);
+    let completed = push_all(
+        &mut assembler,
+        vec![
+            stdout("2024-05-01T12:00:00.1"),
+            stdout("00000000Z ready\n2024-05-01T12:00:01Z\n"),
+        ],
+    );
+    let ready =
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap() + chrono::Duration::milliseconds(100);
+    assert_eq!(
+        completed,
+        [
+            vec![],
+            vec![
+                LogLine {
+                    timestamp: Some(ready),
+                    ..line(Stream::StdOut, "ready")
+                },
+                LogLine {
+                    timestamp: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 1).single(),
+                    ..line(Stream::StdOut, "")
+                },
+            ],
+        ]
+    );
+}
+
+#[test]
+fn keeps_the_raw_bytes_of_invalid_utf8() {
+    let mut assembler = LineAssembler::new(true);
+    let lines = assembler.push(TtyChunk::StdOut(
+        b"2024-05-01T12:00:00Z caf\xe9 \xff\n".to_vec(),
+    ));
+    assert_eq!(lines.len(), 1);
+    assert_eq!(
+        lines[0].timestamp,
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).single()
+    );
+    assert_eq!(lines[0].bytes, b"caf\xe9 \xff");
+    assert_eq!(lines[0].message(), "caf\u{fffd} \u{fffd}");
+}
@@ -0,0 +1,246 @@
+use chrono::{TimeZone, Utc};
+use dockerlogs::containers::ContainerMeta;
+use dockerlogs::grep::LineFilter;
+use dockerlogs::lines::Stream;
+use dockerlogs::output::{OutputFormat, Printer};
+use dockerlogs::source::LogEvent;
+use dockerlogs::template::Template;
+use dockerlogs::timestamps::TimeArgs;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn meta(host: Option<&str>) -> Arc<ContainerMeta> {
+    Arc::new(ContainerMeta {
+        host: host.map(str::to_string),
+        id: "0123456789abcdef".to_string(),
+        name: "api".to_string(),
+        image: Some("shop/api:1.2".to_string()),
+        labels: HashMap::from([("tier".to_string(), "backend".to_string())]),
+    })
+}
+
+fn printer(output: OutputFormat) -> Printer {
+    // Compare plain text, colours are the terminal's business
+    colored::control::set_override(false);
+    Printer {
+        line_filter: LineFilter::default(),
+        time: TimeArgs::default(),
+        output,
+        format: None,
+        sanitize: false,
+    }
+}
+
+fn event(stream: Stream, second: u32, message: &str) -> LogEvent {
+    LogEvent {
+        container: meta(None),
+        stream,
+        timestamp: Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, second).single(),
+        bytes: message.as_bytes().to_vec(),
+    }
+}
+
+/// Renders the events of one container and keeps the text only.
+fn render(printer: &Printer, events: Vec<LogEvent>) -> Vec<String> {
+    let mut context = printer.line_filter.context_buffer();
+    events
+        .into_iter()
+        .flat_map(|event| printer.log_lines(&mut context, 0, event))
+        .map(|(_, text)| text)
+        .collect()
+}
+
+#[test]
+fn text_lines_carry_the_name_and_stream() {
+    let printer = printer(OutputFormat::Text);
+    let lines = render(
+        &printer,
+        vec![
+            event(Stream::StdOut, 0, "listening"),
+            event(Stream::StdErr, 1, "warning: slow"),
+        ],
+    );
+    assert_eq!(lines, ["api o: listening", "api e: warning: slow"]);
+}
+
+#[test]
+fn text_lines_show_timestamps_when_asked() {
+    let mut printer = printer(OutputFormat::Text);
+    printer.time.timestamps = true;
+    let lines = render(&printer, vec![event(Stream::StdOut, 7, "tick")]);
+    assert_eq!(lines, ["api o: 2024-05-01T12:00:07.000Z tick"]);
+}
+
+#[test]
+fn json_lines_carry_the_container_and_timestamp() {
+    let printer = printer(OutputFormat::Json);
+    let lines = render(&printer, vec![event(Stream::StdErr, 3, "boom")]);
+    let value: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "type": "log",
+            "id": "0123456789abcdef",
+            "name": "api",
+            "image": "shop/api:1.2",
+            "stream": "stderr",
+            "timestamp": "2024-05-01T12:00:03Z",
+            "message": "boom",
+        })
+    );
+}
+
+#[test]
+fn grep_keeps_matching_lines_and_their_context() {
+    let mut printer = printer(OutputFormat::Text);
+    printer.line_filter = LineFilter::new(&["ERROR".to_string()], &[])
+        .unwrap()
+        .with_context(1, 0);
+    let lines = render(
+        &printer,
+        vec![
+            event(Stream::StdOut, 0, "one"),
+            event(Stream::StdOut, 1, "two"),
+            event(Stream::StdOut, 2, "ERROR first"),
+            event(Stream::StdOut, 3, "three"),
+            event(Stream::StdOut, 4, "four"),
+            event(Stream::StdOut, 5, "ERROR second"),
+        ],
+    );
+    assert_eq!(
+        lines,
+        [
+            "api o- two",
+            "api o: ERROR first",
+            "api --",
+            "api o- four",
+            "api o: ERROR second",
+        ]
+    );
+}
+
+#[test]
+fn grep_drops_excluded_lines() {
+    let mut printer = printer(OutputFormat::Text);
+    printer.line_filter = LineFilter::new(&[], &["health".to_string()]).unwrap();
+    let lines = render(
+        &printer,
+        vec![
+            event(Stream::StdOut, 0, "GET /health 200"),
+            event(Stream::StdOut, 1, "GET /orders 200"),
+        ],
+    );
+    assert_eq!(lines, ["api o: GET /orders 200"]);
+}
+
+#[test]
+fn sanitize_drops_escape_sequences_before_grep() {
+    let mut printer = printer(OutputFormat::Text);
+    printer.sanitize = true;
+    printer.line_filter = LineFilter::new(&["ERROR timeout".to_string()], &[]).unwrap();
+    let lines = render(
+        &printer,
+        vec![
+            event(
+                Stream::StdErr,
+                0,
+                "\x1b[31mERROR\x1b[0m timeout\x1b]0;pwned\x07",
+            ),
+            event(
+                Stream::StdErr,
+                1,
+                "\u{9b}31mERROR\u{9b}0m timeout\x1bP+q\x1b\\\r",
+            ),
+            event(Stream::StdOut, 2, "GET /orders 200"),
+        ],
+    );
+    assert_eq!(lines, ["api e: ERROR timeout", "api e: ERROR timeout"]);
+}
+
+#[test]
+fn json_context_lines_are_flagged_instead_of_separated() {
+    let mut printer = printer(OutputFormat::Json);
+    printer.line_filter = LineFilter::new(&["ERROR".to_string()], &[])
+        .unwrap()
+        .with_context(1, 0);
+    let lines = render(
+        &printer,
+        vec![
+            event(Stream::StdOut, 0, "before"),
+            event(Stream::StdOut, 1, "ERROR"),
+        ],
+    );
+    let context: Vec<bool> = lines
+        .iter()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .map(|value| value.get("context").is_some())
+        .collect();
+    assert_eq!(context, [true, false]);
+}
+
+#[test]
+fn templates_render_fields_and_alignment() {
+    let mut printer = printer(OutputFormat::Text);
+    printer.format =
+        Some(Template::parse("[{name:<6}] {id:4} {label:tier} {stream:>6} {{{msg}}}").unwrap());
+    let lines = render(&printer, vec![event(Stream::StdOut, 0, "hi")]);
+    assert_eq!(lines, ["[api   ] 0123 backend stdout {hi}"]);
+}
+
+#[test]
+fn templates_tell_grep_context_from_matches() {
+    let mut printer = printer(OutputFormat::Text);
+    printer.format = Some(Template::parse("{name}{sep} {msg}").unwrap());
+    printer.line_filter = LineFilter::new(&["ERROR".to_string()], &[])
+        .unwrap()
+        .with_context(1, 0);
+    let lines = render(
+        &printer,
+        vec![
+            event(Stream::StdOut, 0, "one"),
+            event(Stream::StdOut, 1, "ERROR first"),
+            event(Stream::StdOut, 2, "two"),
+            event(Stream::StdOut, 3, "three"),
+            event(Stream::StdOut, 4, "ERROR second"),
+        ],
+    );
+    assert_eq!(
+        lines,
+        [
+            "api- one",
+            "api: ERROR first",
+            "--",
+            "api- three",
+            "api: ERROR second",
+        ]
+    );
+}
+
+#[test]
+fn host_aliases_prefix_container_names() {
+    let printer = printer(OutputFormat::Text);
+    let mut event = event(Stream::StdOut, 0, "hi");
+    event.container = meta(Some("prod"));
+    assert_eq!(render(&printer, vec![event]), ["prod/api o: hi"]);
+    assert_eq!(
+        printer.started(&meta(Some("prod"))),
+        ">>> ✓ Started watching container prod/api"
+    );
+}
+
+#[test]
+fn lifecycle_notices_as_json() {
+    let printer = printer(OutputFormat::Json);
+    assert_eq!(
+        printer.stopped(&meta(None)),
+        r#"{"type":"stopped","id":"0123456789abcdef","name":"api","image":"shop/api:1.2"}"#
+    );
+    assert_eq!(
+        printer.daemon_notice(Some("prod"), false),
+        r#"{"type":"disconnected","host":"prod"}"#
+    );
+    assert_eq!(
+        printer.daemon_notice(None, true),
+        r#"{"type":"reconnected"}"#
+    );
+}
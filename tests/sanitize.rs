@@ -0,0 +1,169 @@
+//! Property tests feeding random terminal output, heavy on escape sequences,
+//! through the sanitiser and the TUI parser.
+
+use dockerlogs::ansi::{self, Parser, StyledText, Token};
+
+const CASES: usize = 20_000;
+
+/// Characters random input is made of, weighted towards the ones that start,
+/// continue or end escape sequences.
+const ALPHABET: &[char] = &[
+    '\x1b', '\x1b', '\x1b', '\x1b', '[', '[', '[', ']', ']', 'P', 'X', '^', '_', '\\', '(', '#',
+    ' ', '$', '\x07', '\x18', '\x1a', '\t', '\n', '\r', '\x08', '\0', '\x7f', '0', '1', '2', '3',
+    '5', '8', '9', ';', ';', ':', '?', '<', 'm', 'm', 'm', 'H', 'K', 'q', 'a', 'z', '~', '\u{80}',
+    '\u{85}', '\u{90}', '\u{98}', '\u{9b}', '\u{9b}', '\u{9c}', '\u{9c}', '\u{9d}', '\u{9e}',
+    '\u{9f}', 'é', '✓', '界', '🐳',
+];
+
+/// xorshift64, enough randomness to explore the parser and reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn line(&mut self) -> String {
+        let len = self.below(40);
+        (0..len)
+            .map(|_| ALPHABET[self.below(ALPHABET.len())])
+            .collect()
+    }
+}
+
+/// Runs `check` on random lines, naming the failing one.
+fn for_random_lines(seed: u64, check: impl Fn(&str) -> Result<(), String>) {
+    let mut rng = Rng(seed);
+    for _ in 0..CASES {
+        let line = rng.line();
+        if let Err(message) = check(&line) {
+            panic!("{} for input {:?}", message, line);
+        }
+    }
+}
+
+fn ensure(condition: bool, message: impl FnOnce() -> String) -> Result<(), String> {
+    if condition {
+        Ok(())
+    } else {
+        Err(message())
+    }
+}
+
+#[test]
+fn sanitized_lines_hold_no_control_characters() {
+    for_random_lines(0x9e37_79b9_7f4a_7c15, |line| {
+        let clean = ansi::sanitize(line);
+        ensure(!clean.chars().any(|c| c.is_control() && c != '\t'), || {
+            format!("{:?} holds a control character", clean)
+        })
+    });
+}
+
+#[test]
+fn parsed_lines_hold_no_control_characters() {
+    for_random_lines(0xdead_beef_cafe_f00d, |line| {
+        let text = StyledText::parse(line).text;
+        ensure(!text.chars().any(char::is_control), || {
+            format!("{:?} holds a control character", text)
+        })
+    });
+}
+
+#[test]
+fn sanitizing_twice_changes_nothing() {
+    for_random_lines(0x0123_4567_89ab_cdef, |line| {
+        let once = ansi::sanitize(line);
+        let twice = ansi::sanitize(&once);
+        ensure(once == twice, || format!("{:?} became {:?}", once, twice))?;
+        let stripped = ansi::strip(line);
+        let again = ansi::strip(&stripped);
+        ensure(stripped == again, || {
+            format!("{:?} became {:?}", stripped, again)
+        })
+    });
+}
+
+#[test]
+fn styles_cover_the_text_in_order() {
+    for_random_lines(0x5555_aaaa_3333_cccc, |line| {
+        let parsed = StyledText::parse(line);
+        let mut end = 0;
+        for (range, _) in &parsed.styles {
+            ensure(
+                end <= range.start && range.start < range.end && range.end <= parsed.text.len(),
+                || format!("style run {:?} out of place", range),
+            )?;
+            ensure(
+                parsed.text.is_char_boundary(range.start)
+                    && parsed.text.is_char_boundary(range.end),
+                || format!("style run {:?} splits a character", range),
+            )?;
+            end = range.end;
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn sanitizing_keeps_the_parsed_text() {
+    // Both modes see the same text, only tabs and newlines are shown differently
+    for_random_lines(0x1357_9bdf_2468_ace0, |line| {
+        let sanitized = ansi::sanitize(line).replace('\t', "    ");
+        let parsed = StyledText::parse(line).text.replace(' ', "");
+        ensure(sanitized.replace(' ', "") == parsed, || {
+            format!("{:?} differs from {:?}", sanitized, parsed)
+        })
+    });
+}
+
+#[test]
+fn parser_only_emits_printable_text() {
+    // Every character on its own, in every state the parser can be in
+    let prefixes = [
+        "", "\x1b", "\x1b(", "\x1b[", "\x1b[?", "\x1b]", "\x1bP", "\u{9b}",
+    ];
+    for prefix in prefixes {
+        for code in (0..=0x2000).chain([0xfeff, 0x1f433]) {
+            let Some(c) = char::from_u32(code) else {
+                continue;
+            };
+            let mut parser = Parser::default();
+            for token in prefix.chars().chain([c]).filter_map(|c| parser.feed(c)) {
+                if let Token::Text(text) = token {
+                    assert!(
+                        !text.is_control(),
+                        "{:?} after {:?} came out as text",
+                        c,
+                        prefix
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn plain_text_passes_through() {
+    let mut rng = Rng(0x0f0f_0f0f_0f0f_0f0f);
+    let printable: Vec<char> = ALPHABET
+        .iter()
+        .copied()
+        .filter(|c| !c.is_control() && *c != '\x1b')
+        .collect();
+    for _ in 0..CASES {
+        let len = rng.below(40);
+        let line: String = (0..len)
+            .map(|_| printable[rng.below(printable.len())])
+            .collect();
+        assert_eq!(ansi::sanitize(&line), line);
+        assert_eq!(ansi::strip(&line), line);
+    }
+}
@@ -0,0 +1,702 @@
+use dockerlogs::backend::Backend;
+use dockerlogs::containers::{ContainerCache, ContainerMeta};
+use dockerlogs::endpoint::Endpoint;
+use dockerlogs::fake::{ApiCalls, FakeBackend, FakeContainer};
+use dockerlogs::lines::Stream;
+use dockerlogs::selection::{SelectionArgs, Selector, StatusArg};
+use dockerlogs::source::{self, LogSource, SourceEvent, SourceOptions, Subscription};
+use dockerlogs::timestamps::TimeArgs;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn options(follow: bool) -> SourceOptions {
+    SourceOptions {
+        follow,
+        last_n_lines: Some(100),
+        stdout: true,
+        stderr: true,
+        time: TimeArgs::default(),
+        resync_interval: None,
+    }
+}
+
+fn endpoint() -> Endpoint {
+    Endpoint {
+        alias: None,
+        url: "unix:///fake.sock".to_string(),
+        tls: None,
+        probe: false,
+    }
+}
+
+async fn subscribe(fake: &FakeBackend, selector: Selector, options: SourceOptions) -> Subscription {
+    let (sink, subscription) = source::channel();
+    LogSource::new(Arc::new(fake.clone()), &endpoint(), selector, options)
+        .start(&sink)
+        .await
+        .expect("listing the fake never fails");
+    subscription
+}
+
+fn select_all() -> Selector {
+    Selector::new(".*", false, &SelectionArgs::default()).unwrap()
+}
+
+async fn next(subscription: &mut Subscription) -> SourceEvent {
+    tokio::time::timeout(TIMEOUT, subscription.next())
+        .await
+        .expect("timed out waiting for an event")
+        .expect("the subscription ended")
+}
+
+/// Every event until the subscription ends, which it does when not following.
+async fn collect(mut subscription: Subscription) -> Vec<SourceEvent> {
+    let mut events = Vec::new();
+    while let Some(event) = tokio::time::timeout(TIMEOUT, subscription.next())
+        .await
+        .expect("timed out waiting for the subscription to end")
+    {
+        events.push(event);
+    }
+    events
+}
+
+/// A compact form of the events that is easy to compare.
+fn describe(event: &SourceEvent) -> String {
+    match event {
+        SourceEvent::Started(meta) => format!("started {}", meta.display_name()),
+        SourceEvent::Log(event) => format!(
+            "{} {}: {}",
+            event.container.display_name(),
+            event.stream.name(),
+            event.message()
+        ),
+        SourceEvent::Stopped {
+            container,
+            exit_code,
+            error,
+        } => format!(
+            "stopped {} {:?} {}",
+            container.display_name(),
+            exit_code,
+            error.is_some()
+        ),
+        SourceEvent::Removed(meta) => format!("removed {}", meta.display_name()),
+        SourceEvent::Disconnected { .. } => "disconnected".to_string(),
+        SourceEvent::Reconnected { .. } => "reconnected".to_string(),
+    }
+}
+
+/// The descriptions of one container's events, in order.
+fn events_of(events: &[SourceEvent], name: &str) -> Vec<String> {
+    events
+        .iter()
+        .map(describe)
+        .filter(|description| {
+            description
+                .split_whitespace()
+                .any(|word| word.trim_end_matches(':') == name)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn lists_running_containers_and_reads_their_logs() {
+    let fake = FakeBackend::new();
+    fake.add(
+        FakeContainer::new("a1", "api")
+            .line(Stream::StdOut, "listening")
+            .line(Stream::StdErr, "warning: slow"),
+    );
+    fake.add(FakeContainer::new("d1", "db").line(Stream::StdOut, "ready"));
+    fake.add(
+        FakeContainer::new("o1", "old")
+            .exited(1)
+            .line(Stream::StdOut, "bye"),
+    );
+
+    let events = collect(subscribe(&fake, select_all(), options(false)).await).await;
+
+    assert_eq!(
+        events_of(&events, "api"),
+        [
+            "started api",
+            "api stdout: listening",
+            "api stderr: warning: slow",
+            "stopped api None false",
+        ]
+    );
+    assert_eq!(
+        events_of(&events, "db"),
+        ["started db", "db stdout: ready", "stopped db None false"]
+    );
+    assert!(events_of(&events, "old").is_empty());
+}
+
+#[tokio::test]
+async fn includes_stopped_containers_with_all() {
+    let fake = FakeBackend::new();
+    fake.add(
+        FakeContainer::new("o1", "old")
+            .exited(1)
+            .line(Stream::StdOut, "bye"),
+    );
+
+    let selector = Selector::new(".*", true, &SelectionArgs::default()).unwrap();
+    let events = collect(subscribe(&fake, selector, options(false)).await).await;
+
+    assert_eq!(
+        events_of(&events, "old"),
+        ["started old", "old stdout: bye", "stopped old None false"]
+    );
+}
+
+#[tokio::test]
+async fn filters_containers_by_name_and_image() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api-1").image("shop/api"));
+    fake.add(FakeContainer::new("a2", "api-2").image("shop/api"));
+    fake.add(FakeContainer::new("a3", "api-canary").image("shop/api"));
+    fake.add(FakeContainer::new("w1", "worker").image("shop/worker"));
+    fake.add(FakeContainer::new("p1", "api-proxy").image("nginx"));
+
+    let args = SelectionArgs {
+        image: Some("^shop/".to_string()),
+        exclude_regex: Some("canary".to_string()),
+        ..SelectionArgs::default()
+    };
+    let selector = Selector::new("^api", false, &args).unwrap();
+    let events = collect(subscribe(&fake, selector, options(false)).await).await;
+
+    let mut started: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            SourceEvent::Started(meta) => Some(meta.name.clone()),
+            _ => None,
+        })
+        .collect();
+    started.sort();
+    assert_eq!(started, ["api-1", "api-2"]);
+}
+
+#[tokio::test]
+async fn filters_containers_by_label() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api").label("com.docker.compose.project", "shop"));
+    fake.add(FakeContainer::new("b1", "blog").label("com.docker.compose.project", "blog"));
+
+    let args = SelectionArgs {
+        project: Some("shop".to_string()),
+        ..SelectionArgs::default()
+    };
+    let selector = Selector::new(".*", false, &args).unwrap();
+    let events = collect(subscribe(&fake, selector, options(false)).await).await;
+
+    assert_eq!(
+        events_of(&events, "api"),
+        ["started api", "stopped api None false"]
+    );
+    assert!(events_of(&events, "blog").is_empty());
+}
+
+/// The names the fake lists for the filters of `selector`, unchecked by the
+/// selector itself.
+async fn listed(fake: &FakeBackend, selector: &Selector) -> Vec<String> {
+    let mut names: Vec<String> = fake
+        .list(&selector.list_opts())
+        .await
+        .unwrap()
+        .into_iter()
+        .flat_map(|summary| summary.names.unwrap_or_default())
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn pushes_label_status_and_name_filters_down_to_the_list() {
+    let fake = FakeBackend::new();
+    let project = "com.docker.compose.project";
+    fake.add(FakeContainer::new("a1", "api").label(project, "shop"));
+    fake.add(
+        FakeContainer::new("a2", "api-old")
+            .label(project, "shop")
+            .exited(0),
+    );
+    fake.add(FakeContainer::new("w1", "worker").label(project, "shop"));
+    fake.add(FakeContainer::new("b1", "api-blog").label(project, "blog"));
+    fake.add(FakeContainer::new("n1", "api-bare"));
+
+    let shop = SelectionArgs {
+        project: Some("shop".to_string()),
+        ..SelectionArgs::default()
+    };
+    let selector = Selector::new("^api", false, &shop).unwrap();
+    assert_eq!(listed(&fake, &selector).await, ["/api"]);
+
+    let exited = SelectionArgs {
+        status: vec![StatusArg::Exited],
+        ..shop
+    };
+    let selector = Selector::new("^api", false, &exited).unwrap();
+    assert_eq!(listed(&fake, &selector).await, ["/api-old"]);
+
+    let labelled = SelectionArgs {
+        label: vec![project.to_string()],
+        ..SelectionArgs::default()
+    };
+    let selector = Selector::new("blog$", true, &labelled).unwrap();
+    assert_eq!(listed(&fake, &selector).await, ["/api-blog"]);
+}
+
+#[tokio::test]
+async fn keeps_regex_syntax_docker_may_not_know_client_side() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api-1"));
+    fake.add(FakeContainer::new("w1", "worker"));
+
+    // Verbose mode is Rust's alone, Docker would reject or misread it
+    let selector = Selector::new(r"(?x) ^ api - \d", false, &SelectionArgs::default()).unwrap();
+    assert_eq!(listed(&fake, &selector).await, ["/api-1", "/worker"]);
+    let events = collect(subscribe(&fake, selector, options(false)).await).await;
+    assert_eq!(
+        events_of(&events, "api-1"),
+        ["started api-1", "stopped api-1 None false"]
+    );
+    assert!(events_of(&events, "worker").is_empty());
+}
+
+#[tokio::test]
+async fn pushes_label_filters_down_to_the_events() {
+    let fake = FakeBackend::new();
+    let project = "com.docker.compose.project";
+    let shop = SelectionArgs {
+        project: Some("shop".to_string()),
+        ..SelectionArgs::default()
+    };
+    let selector = Selector::new(".*", false, &shop).unwrap();
+    let mut events = fake.events(&selector.events_opts(None));
+
+    fake.start(FakeContainer::new("a1", "api").label(project, "shop"));
+    fake.start(FakeContainer::new("b1", "blog").label(project, "blog"));
+    fake.start(FakeContainer::new("n1", "bare"));
+    fake.start(FakeContainer::new("w1", "worker").label(project, "shop"));
+
+    let mut started = Vec::new();
+    while let Ok(Some(event)) =
+        tokio::time::timeout(Duration::from_millis(200), events.next()).await
+    {
+        let actor = event.unwrap().actor.unwrap();
+        started.push(actor.attributes.unwrap()["name"].clone());
+    }
+    assert_eq!(started, ["api", "worker"]);
+}
+
+#[tokio::test]
+async fn reads_only_the_requested_streams() {
+    let fake = FakeBackend::new();
+    fake.add(
+        FakeContainer::new("a1", "api")
+            .line(Stream::StdOut, "out")
+            .line(Stream::StdErr, "err"),
+    );
+
+    let options = SourceOptions {
+        stdout: false,
+        ..options(false)
+    };
+    let events = collect(subscribe(&fake, select_all(), options).await).await;
+
+    assert_eq!(
+        events_of(&events, "api"),
+        ["started api", "api stderr: err", "stopped api None false"]
+    );
+}
+
+#[tokio::test]
+async fn keeps_only_the_last_lines() {
+    let fake = FakeBackend::new();
+    let mut container = FakeContainer::new("a1", "api");
+    for i in 0..10 {
+        container = container.line(Stream::StdOut, &format!("line {}", i));
+    }
+    fake.add(container);
+
+    let options = SourceOptions {
+        last_n_lines: Some(2),
+        ..options(false)
+    };
+    let events = collect(subscribe(&fake, select_all(), options).await).await;
+
+    assert_eq!(
+        events_of(&events, "api"),
+        [
+            "started api",
+            "api stdout: line 8",
+            "api stdout: line 9",
+            "stopped api None false",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn trims_lines_to_a_sub_second_window() {
+    let second = chrono::Utc::now() - chrono::Duration::minutes(1);
+    let second = second - chrono::Duration::nanoseconds(second.timestamp_subsec_nanos().into());
+    let at = |millis| second + chrono::Duration::milliseconds(millis);
+    let fake = FakeBackend::new();
+    fake.add(
+        FakeContainer::new("a1", "api")
+            .line_at(Stream::StdOut, at(200), "too early")
+            .line_at(Stream::StdOut, at(500), "within")
+            .line_at(Stream::StdOut, at(800), "too late"),
+    );
+
+    let options = SourceOptions {
+        last_n_lines: None,
+        time: TimeArgs {
+            since: Some(at(400)),
+            until: Some(at(600)),
+            ..TimeArgs::default()
+        },
+        ..options(false)
+    };
+    let events = collect(subscribe(&fake, select_all(), options).await).await;
+
+    assert_eq!(
+        events_of(&events, "api"),
+        [
+            "started api",
+            "api stdout: within",
+            "stopped api None false"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn follows_containers_started_later_until_they_stop() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api"));
+
+    let selector = Selector::new(".*", true, &SelectionArgs::default()).unwrap();
+    let mut subscription = subscribe(&fake, selector, options(true)).await;
+    assert_eq!(describe(&next(&mut subscription).await), "started api");
+
+    fake.subscribed().await;
+    fake.start(FakeContainer::new("w1", "worker"));
+    assert_eq!(describe(&next(&mut subscription).await), "started worker");
+
+    fake.log("w1", Stream::StdOut, "job done");
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "worker stdout: job done"
+    );
+
+    fake.stop("w1", 3);
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "stopped worker Some(3) false"
+    );
+}
+
+#[tokio::test]
+async fn ignores_started_containers_that_are_not_selected() {
+    let fake = FakeBackend::new();
+    let selector = Selector::new("^api", false, &SelectionArgs::default()).unwrap();
+    let mut subscription = subscribe(&fake, selector, options(true)).await;
+
+    fake.subscribed().await;
+    fake.start(FakeContainer::new("w1", "worker"));
+    fake.start(FakeContainer::new("a1", "api"));
+
+    assert_eq!(describe(&next(&mut subscription).await), "started api");
+}
+
+#[tokio::test]
+async fn resync_picks_up_missed_containers_and_drops_removed_ones() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api"));
+
+    let options = SourceOptions {
+        resync_interval: Some(Duration::from_millis(50)),
+        ..options(true)
+    };
+    let mut subscription = subscribe(&fake, select_all(), options).await;
+    assert_eq!(describe(&next(&mut subscription).await), "started api");
+
+    // Added without a start event, only a resync can find it
+    fake.add(FakeContainer::new("w1", "worker"));
+    assert_eq!(describe(&next(&mut subscription).await), "started worker");
+
+    fake.remove("a1");
+    let mut gone = vec![
+        describe(&next(&mut subscription).await),
+        describe(&next(&mut subscription).await),
+    ];
+    gone.sort();
+    assert_eq!(gone, ["removed api", "stopped api None false"]);
+}
+
+#[tokio::test]
+async fn resync_keeps_containers_started_after_the_listing() {
+    let fake = FakeBackend::new();
+    let options = SourceOptions {
+        resync_interval: Some(Duration::from_millis(50)),
+        ..options(true)
+    };
+    let mut subscription = subscribe(&fake, select_all(), options).await;
+    fake.subscribed().await;
+
+    // Missing from the resync listing, yet watched before the resync is done
+    fake.start_during_next_list(FakeContainer::new("w1", "worker"));
+    assert_eq!(describe(&next(&mut subscription).await), "started worker");
+
+    // Several resyncs later it is still followed, and never reported gone
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    fake.log("w1", Stream::StdOut, "job done");
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "worker stdout: job done"
+    );
+    assert_eq!(fake.calls().logs, 1);
+}
+
+#[tokio::test]
+async fn resync_leaves_streams_ended_by_until_alone() {
+    let now = chrono::Utc::now();
+    let fake = FakeBackend::new();
+    fake.add(
+        FakeContainer::new("a1", "api")
+            .line_at(
+                Stream::StdOut,
+                now - chrono::Duration::seconds(20),
+                "before",
+            )
+            .line_at(Stream::StdOut, now - chrono::Duration::seconds(1), "after"),
+    );
+
+    let options = SourceOptions {
+        time: TimeArgs {
+            until: Some(now - chrono::Duration::seconds(10)),
+            ..TimeArgs::default()
+        },
+        resync_interval: Some(Duration::from_millis(50)),
+        ..options(true)
+    };
+    let mut subscription = subscribe(&fake, select_all(), options).await;
+    let mut seen = Vec::new();
+    for _ in 0..3 {
+        seen.push(describe(&next(&mut subscription).await));
+    }
+    assert_eq!(
+        seen,
+        [
+            "started api",
+            "api stdout: before",
+            "stopped api None false"
+        ]
+    );
+
+    // The container still runs, yet several resyncs later it is not watched again
+    let quiet = tokio::time::timeout(Duration::from_millis(300), subscription.next()).await;
+    assert!(
+        quiet.is_err(),
+        "unexpected {:?}",
+        quiet.map(|e| e.map(|e| describe(&e)))
+    );
+    assert_eq!(fake.calls().logs, 1);
+}
+
+#[tokio::test]
+async fn watched_containers_are_never_inspected() {
+    let fake = FakeBackend::new();
+    for i in 0..20 {
+        fake.add(FakeContainer::new(
+            &format!("a{}", i),
+            &format!("api-{}", i),
+        ));
+    }
+
+    let mut subscription = subscribe(&fake, select_all(), options(true)).await;
+    for _ in 0..20 {
+        assert!(describe(&next(&mut subscription).await).starts_with("started api-"));
+    }
+    fake.subscribed().await;
+    fake.start(FakeContainer::new("w1", "worker"));
+    assert_eq!(describe(&next(&mut subscription).await), "started worker");
+    fake.log("w1", Stream::StdOut, "job done");
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "worker stdout: job done"
+    );
+
+    // One listing and one event stream for the whole daemon, names come
+    // from list and event data
+    assert_eq!(
+        fake.calls(),
+        ApiCalls {
+            list: 1,
+            inspect: 0,
+            logs: 21,
+            events: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn cache_forgets_removed_containers() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api"));
+    let cache = ContainerCache::new(Some("prod".to_string()));
+    cache
+        .insert(ContainerMeta {
+            host: None,
+            id: "a1".to_string(),
+            name: "api".to_string(),
+            image: None,
+            labels: HashMap::new(),
+        })
+        .await;
+
+    let meta = cache.get(&fake, "a1").await.unwrap();
+    assert_eq!(meta.display_name(), "prod/api");
+    assert_eq!(fake.calls().inspect, 0);
+
+    cache.remove("a1").await;
+    let meta = cache.get(&fake, "a1").await.unwrap();
+    assert_eq!(meta.display_name(), "prod/api");
+    assert_eq!(fake.calls().inspect, 1);
+}
+
+#[tokio::test]
+async fn resumes_a_dropped_log_stream_without_duplicates_or_gaps() {
+    let fake = FakeBackend::new();
+    let mut container = FakeContainer::new("a1", "api");
+    // Written within the same second, which is all Docker's `since` can tell apart
+    for i in 0..5 {
+        container = container.line(Stream::StdOut, &format!("line {}", i));
+    }
+    fake.add(container);
+
+    let mut subscription = subscribe(&fake, select_all(), options(true)).await;
+    assert_eq!(describe(&next(&mut subscription).await), "started api");
+    for i in 0..5 {
+        assert_eq!(
+            describe(&next(&mut subscription).await),
+            format!("api stdout: line {}", i)
+        );
+    }
+
+    fake.drop_streams();
+    // Logged while nobody is reading
+    fake.log("a1", Stream::StdOut, "line 5");
+    fake.log("a1", Stream::StdOut, "line 6");
+    let mut seen = Vec::new();
+    while !seen.iter().any(|event| event == "api stdout: line 7") {
+        seen.push(describe(&next(&mut subscription).await));
+        if seen.last().unwrap() == "api stdout: line 6" {
+            fake.log("a1", Stream::StdOut, "line 7");
+        }
+    }
+    // The events stream was dropped too and reports the daemon notices
+    seen.retain(|event| !event.ends_with("connected"));
+    assert_eq!(
+        seen,
+        [
+            "api stdout: line 5",
+            "api stdout: line 6",
+            "api stdout: line 7"
+        ]
+    );
+    assert_eq!(fake.calls().logs, 2);
+}
+
+#[tokio::test]
+async fn reconnects_after_the_daemon_goes_away() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api").line(Stream::StdOut, "before"));
+
+    let mut subscription = subscribe(&fake, select_all(), options(true)).await;
+    assert_eq!(describe(&next(&mut subscription).await), "started api");
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "api stdout: before"
+    );
+    fake.subscribed().await;
+    fake.start(FakeContainer::new("w0", "worker-0"));
+    assert_eq!(describe(&next(&mut subscription).await), "started worker-0");
+    fake.stop("w0", 0);
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "stopped worker-0 None false"
+    );
+
+    fake.disconnect();
+    assert_eq!(describe(&next(&mut subscription).await), "disconnected");
+    // Happens while the daemon cannot be reached, only replayed events can tell
+    fake.log("a1", Stream::StdOut, "during");
+    fake.start(FakeContainer::new("w1", "worker-1"));
+    fake.restore();
+
+    let mut events = Vec::new();
+    for _ in 0..3 {
+        events.push(describe(&next(&mut subscription).await));
+    }
+    // The log stream resumes on its own, possibly before the events do
+    let position = |wanted: &str| {
+        events
+            .iter()
+            .position(|event| event == wanted)
+            .unwrap_or_else(|| panic!("no {:?} in {:?}", wanted, events))
+    };
+    position("api stdout: during");
+    assert!(position("reconnected") < position("started worker-1"));
+
+    // Nothing seen before the disconnection comes again, not even the
+    // start of the worker that stopped since
+    fake.log("a1", Stream::StdOut, "after");
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "api stdout: after"
+    );
+    let quiet = tokio::time::timeout(Duration::from_millis(300), subscription.next()).await;
+    assert!(
+        quiet.is_err(),
+        "unexpected {:?}",
+        quiet.map(|e| e.map(|e| describe(&e)))
+    );
+}
+
+#[tokio::test]
+async fn drops_a_partial_line_cut_by_a_dropped_stream() {
+    let fake = FakeBackend::new();
+    fake.add(FakeContainer::new("a1", "api").line(Stream::StdOut, "ready"));
+
+    let mut subscription = subscribe(&fake, select_all(), options(true)).await;
+    assert_eq!(describe(&next(&mut subscription).await), "started api");
+    assert_eq!(
+        describe(&next(&mut subscription).await),
+        "api stdout: ready"
+    );
+
+    // Half of the line arrives, then all of it again once resumed
+    fake.tear_next_line();
+    fake.log("a1", Stream::StdOut, "a rather long line cut in two");
+    fake.log("a1", Stream::StdOut, "next");
+    let mut seen = Vec::new();
+    while !seen.iter().any(|event| event == "api stdout: next") {
+        seen.push(describe(&next(&mut subscription).await));
+    }
+    seen.retain(|event| !event.ends_with("connected"));
+    assert_eq!(
+        seen,
+        [
+            "api stdout: a rather long line cut in two",
+            "api stdout: next"
+        ]
+    );
+}
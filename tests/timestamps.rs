@@ -0,0 +1,88 @@
+use chrono::{Duration, TimeZone, Utc};
+use docker_api::opts::LogsOpts;
+use dockerlogs::timestamps::{parse_time, TimeArgs};
+
+#[test]
+fn parses_rfc3339_in_any_offset() {
+    let expected = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    assert_eq!(parse_time("2024-05-01T12:00:00Z"), Ok(expected));
+    assert_eq!(parse_time("2024-05-01T14:00:00+02:00"), Ok(expected));
+    assert_eq!(
+        parse_time(" 2024-05-01T12:00:00.250Z "),
+        Ok(expected + Duration::milliseconds(250))
+    );
+}
+
+#[test]
+fn parses_unix_epochs() {
+    let expected = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    assert_eq!(parse_time("1714564800"), Ok(expected));
+    assert_eq!(
+        parse_time("1714564800.5"),
+        Ok(expected + Duration::milliseconds(500))
+    );
+    // Digits beyond nanoseconds are dropped
+    assert_eq!(
+        parse_time("1714564800.0000000019"),
+        Ok(expected + Duration::nanoseconds(1))
+    );
+}
+
+#[test]
+fn parses_durations_relative_to_now() {
+    for (input, ago) in [
+        ("30s", Duration::seconds(30)),
+        ("15m", Duration::minutes(15)),
+        ("1h30m", Duration::minutes(90)),
+        ("2d", Duration::days(2)),
+        ("1w", Duration::weeks(1)),
+    ] {
+        let before = Utc::now();
+        let parsed = parse_time(input).unwrap();
+        let after = Utc::now();
+        assert!(
+            before - ago <= parsed && parsed <= after - ago,
+            "{} gave {}",
+            input,
+            parsed
+        );
+    }
+}
+
+#[test]
+fn rejects_other_input() {
+    for input in [
+        "",
+        "yesterday",
+        "15x",
+        "1h30",
+        "m",
+        "1.5.0",
+        ".5",
+        "1e9",
+        "-15m",
+    ] {
+        let err = parse_time(input).unwrap_err();
+        assert!(err.starts_with("invalid time"), "{}: {}", input, err);
+    }
+}
+
+fn window_query(since: &str, until: &str) -> String {
+    let time = TimeArgs {
+        since: Some(parse_time(since).unwrap()),
+        until: Some(parse_time(until).unwrap()),
+        ..TimeArgs::default()
+    };
+    time.apply(LogsOpts::builder()).build().serialize().unwrap()
+}
+
+#[test]
+fn widens_sub_second_windows_to_whole_seconds() {
+    let query = window_query("1714564800.9", "1714564860.1");
+    assert!(query.contains("since=1714564800"), "{}", query);
+    assert!(query.contains("until=1714564861"), "{}", query);
+
+    let query = window_query("1714564800", "1714564860");
+    assert!(query.contains("since=1714564800"), "{}", query);
+    assert!(query.contains("until=1714564860"), "{}", query);
+}
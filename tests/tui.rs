@@ -0,0 +1,598 @@
+//! Drives the TUI against a fake daemon and ratatui's test backend.
+
+mod common;
+
+use common::{load_fixture, FakeDaemon};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use docker_api::Docker;
+use dockerlogs::backend::Backend;
+use dockerlogs::endpoint::Endpoint;
+use dockerlogs::fake::{FakeBackend, FakeContainer};
+use dockerlogs::lines::Stream;
+use dockerlogs::selection::{SelectionArgs, Selector};
+use dockerlogs::source::SourceOptions;
+use dockerlogs::timestamps::TimeArgs;
+use dockerlogs::tui::App;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Cell;
+use ratatui::style::Color;
+use ratatui::Terminal;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The app following the fixture over the daemon's socket, as `dockerlogs tui` would.
+async fn start(daemon: &FakeDaemon) -> App {
+    start_hosts(&[(None, daemon)]).await
+}
+
+/// The app following several daemons, each under its alias.
+async fn start_hosts(daemons: &[(Option<&str>, &FakeDaemon)]) -> App {
+    let hosts = daemons
+        .iter()
+        .map(|(alias, daemon)| {
+            let backend: Arc<dyn Backend> = Arc::new(Docker::unix(daemon.socket()));
+            let endpoint = Endpoint {
+                alias: alias.map(str::to_string),
+                url: daemon.url(),
+                tls: None,
+                probe: false,
+            };
+            (backend, endpoint)
+        })
+        .collect();
+    let selector = Selector::new(".*", false, &SelectionArgs::default()).unwrap();
+    let options = SourceOptions {
+        follow: true,
+        last_n_lines: Some(100),
+        stdout: true,
+        stderr: true,
+        time: TimeArgs::default(),
+        resync_interval: None,
+    };
+    App::start(hosts, &selector, options)
+        .await
+        .expect("the fake daemon is reachable")
+}
+
+fn terminal() -> Terminal<TestBackend> {
+    Terminal::new(TestBackend::new(100, 30)).unwrap()
+}
+
+/// The rows of the last drawn frame.
+fn screen(terminal: &Terminal<TestBackend>) -> Vec<String> {
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect()
+        })
+        .collect()
+}
+
+fn row_of(screen: &[String], text: &str) -> Option<usize> {
+    screen.iter().position(|row| row.contains(text))
+}
+
+/// Redraws until every text is on screen, as logs arrive in the background.
+async fn wait_for(app: &App, terminal: &mut Terminal<TestBackend>, texts: &[&str]) -> Vec<String> {
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    loop {
+        app.draw(terminal).await.unwrap();
+        let screen = screen(terminal);
+        if texts.iter().all(|text| row_of(&screen, text).is_some()) {
+            return screen;
+        }
+        if tokio::time::Instant::now() > deadline {
+            panic!("{:?} never showed up on\n{}", texts, screen.join("\n"));
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+/// Handles an event like the UI loop does and draws the result.
+async fn send(app: &App, terminal: &mut Terminal<TestBackend>, event: Event) -> Vec<String> {
+    assert!(app.handle_event(event).await);
+    app.draw(terminal).await.unwrap();
+    screen(terminal)
+}
+
+async fn press(app: &App, terminal: &mut Terminal<TestBackend>, code: KeyCode) -> Vec<String> {
+    send(
+        app,
+        terminal,
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE)),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn lists_containers_and_merges_their_logs() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+
+    let screen = wait_for(
+        &app,
+        &mut terminal,
+        &[
+            "listening on :8080",
+            "database system is ready",
+            "GET /health 200",
+            "ERROR deadlock detected",
+        ],
+    )
+    .await;
+
+    assert!(row_of(&screen, "shop-api").is_some());
+    assert!(row_of(&screen, "migrate").is_none());
+    // Lines of both containers interleave by timestamp
+    let order: Vec<usize> = [
+        "listening on :8080",
+        "database system is ready",
+        "GET /orders 200",
+        "ERROR payment gateway timeout",
+        "ERROR deadlock detected",
+        "GET /health 200",
+    ]
+    .iter()
+    .map(|text| row_of(&screen, text).unwrap())
+    .collect();
+    assert!(
+        order.windows(2).all(|pair| pair[0] < pair[1]),
+        "{:?}",
+        order
+    );
+}
+
+#[tokio::test]
+async fn toggles_containers_from_the_keyboard() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(
+        &app,
+        &mut terminal,
+        &["GET /health 200", "ERROR deadlock detected"],
+    )
+    .await;
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('n')).await;
+    assert!(row_of(&screen, "GET /health 200").is_none());
+    assert!(row_of(&screen, "ERROR deadlock detected").is_none());
+
+    // Containers are sorted by name, shop-db comes second
+    press(&app, &mut terminal, KeyCode::Down).await;
+    press(&app, &mut terminal, KeyCode::Down).await;
+    let screen = press(&app, &mut terminal, KeyCode::Char(' ')).await;
+    assert!(row_of(&screen, "GET /health 200").is_none());
+    // With a single container selected its name is left out
+    let row = row_of(&screen, "ERROR deadlock detected").unwrap();
+    assert!(screen[row].contains("e: ERROR deadlock detected"));
+    assert!(!screen[row].contains("shop-db e:"));
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('a')).await;
+    assert!(row_of(&screen, "GET /health 200").is_some());
+}
+
+#[tokio::test]
+async fn shows_containers_started_later() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["GET /health 200"]).await;
+
+    tokio::time::timeout(TIMEOUT, daemon.backend.subscribed())
+        .await
+        .expect("the app never subscribed to events");
+    daemon
+        .backend
+        .start(FakeContainer::new("w4w4w4w4w4w4w4w4", "worker"));
+    daemon
+        .backend
+        .log("w4w4w4w4w4w4w4w4", Stream::StdOut, "job 7 done");
+
+    wait_for(&app, &mut terminal, &["worker", "job 7 done"]).await;
+}
+
+#[tokio::test]
+async fn shows_container_info_and_quits() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["shop-api", "shop-db"]).await;
+
+    press(&app, &mut terminal, KeyCode::Down).await;
+    let screen = press(&app, &mut terminal, KeyCode::Char('i')).await;
+    assert!(row_of(&screen, "CONTAINER INFO").is_some());
+    assert!(row_of(&screen, "Image: shop/api:1.4").is_some());
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('i')).await;
+    assert!(row_of(&screen, "CONTAINER INFO").is_none());
+
+    let quit = Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    assert!(!app.handle_event(quit).await);
+}
+
+#[tokio::test]
+async fn tells_which_daemon_disconnected() {
+    let shop = FakeDaemon::start(load_fixture("shop")).await;
+    let jobs = FakeBackend::new();
+    jobs.add(FakeContainer::new("w1w1w1w1w1w1w1w1", "worker").line(Stream::StdOut, "job done"));
+    let jobs = FakeDaemon::start(jobs).await;
+    let app = start_hosts(&[(Some("shop"), &shop), (Some("jobs"), &jobs)]).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["jobs/worker", "shop/shop-api"]).await;
+
+    jobs.backend.disconnect();
+    let screen = wait_for(&app, &mut terminal, &["Docker daemon jobs disconnected"]).await;
+    assert!(row_of(&screen, "daemon shop").is_none());
+
+    jobs.backend.restore();
+    let screen = wait_for(&app, &mut terminal, &["Docker daemon jobs reconnected"]).await;
+    assert!(row_of(&screen, "disconnected").is_none());
+}
+
+/// A daemon with one container that logged `count` numbered lines.
+async fn numbered_lines(count: usize) -> FakeDaemon {
+    let backend = FakeBackend::new();
+    let mut container = FakeContainer::new("a1a1a1a1a1a1a1a1", "api");
+    for i in 0..count {
+        container = container.line(Stream::StdOut, &format!("line {:02}", i));
+    }
+    backend.add(container);
+    FakeDaemon::start(backend).await
+}
+
+fn scroll(kind: MouseEventKind) -> Event {
+    Event::Mouse(MouseEvent {
+        kind,
+        column: 50,
+        row: 10,
+        modifiers: KeyModifiers::NONE,
+    })
+}
+
+#[tokio::test]
+async fn scrolls_back_through_the_logs() {
+    let daemon = numbered_lines(60).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    // The logs pane has 25 rows
+    let screen = wait_for(&app, &mut terminal, &["line 59"]).await;
+    assert!(row_of(&screen, "line 34").is_none());
+    assert!(row_of(&screen, "paused").is_none());
+
+    let screen = press(&app, &mut terminal, KeyCode::PageUp).await;
+    assert!(row_of(&screen, "line 10").is_some());
+    assert!(row_of(&screen, "line 34").is_some());
+    assert!(row_of(&screen, "line 35").is_none());
+    assert!(row_of(&screen, "paused, 0 new lines").is_some());
+
+    let screen = press(&app, &mut terminal, KeyCode::Home).await;
+    assert!(row_of(&screen, "line 00").is_some());
+    let screen = press(&app, &mut terminal, KeyCode::PageDown).await;
+    assert!(row_of(&screen, "line 00").is_none());
+    assert!(row_of(&screen, "line 49").is_some());
+
+    let screen = press(&app, &mut terminal, KeyCode::End).await;
+    assert!(row_of(&screen, "line 59").is_some());
+
+    let screen = send(&app, &mut terminal, scroll(MouseEventKind::ScrollUp)).await;
+    assert!(row_of(&screen, "line 56").is_some());
+    assert!(row_of(&screen, "line 57").is_none());
+    let screen = send(&app, &mut terminal, scroll(MouseEventKind::ScrollDown)).await;
+    assert!(row_of(&screen, "line 59").is_some());
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('t')).await;
+    assert!(row_of(&screen, "paused").is_none());
+}
+
+#[tokio::test]
+async fn scrolls_back_past_more_rows_than_a_terminal_has() {
+    let backend = FakeBackend::new();
+    // Wraps to 66,000 rows of the 98 columns wide pane
+    let long = "x".repeat(98 * 66_000);
+    backend.add(
+        FakeContainer::new("a1a1a1a1a1a1a1a1", "api")
+            .line(Stream::StdOut, "first")
+            .line(Stream::StdOut, &long)
+            .line(Stream::StdOut, "last"),
+    );
+    let daemon = FakeDaemon::start(backend).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    let screen = wait_for(&app, &mut terminal, &["last"]).await;
+    assert!(row_of(&screen, "first").is_none());
+
+    let screen = press(&app, &mut terminal, KeyCode::Home).await;
+    assert_eq!(row_of(&screen, "first"), Some(1));
+    let screen = press(&app, &mut terminal, KeyCode::End).await;
+    assert!(row_of(&screen, "last").is_some());
+
+    press(&app, &mut terminal, KeyCode::Char('/')).await;
+    type_text(&app, &mut terminal, "first").await;
+    let screen = press(&app, &mut terminal, KeyCode::Enter).await;
+    assert!(row_of(&screen, "first").is_some());
+    assert!(row_of(&screen, "last").is_none());
+}
+
+#[tokio::test]
+async fn pausing_buffers_new_lines_until_resuming() {
+    let daemon = numbered_lines(5).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["line 04"]).await;
+    tokio::time::timeout(TIMEOUT, daemon.backend.subscribed())
+        .await
+        .expect("the app never subscribed to events");
+
+    press(&app, &mut terminal, KeyCode::Char('p')).await;
+    daemon
+        .backend
+        .log("a1a1a1a1a1a1a1a1", Stream::StdOut, "fresh 1");
+    daemon
+        .backend
+        .log("a1a1a1a1a1a1a1a1", Stream::StdOut, "fresh 2");
+    let screen = wait_for(&app, &mut terminal, &["paused, 2 new lines"]).await;
+    assert!(row_of(&screen, "fresh").is_none());
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('p')).await;
+    assert!(row_of(&screen, "fresh 2").is_some());
+    assert!(row_of(&screen, "paused").is_none());
+}
+
+#[tokio::test]
+async fn counts_only_filtered_lines_while_paused() {
+    let daemon = numbered_lines(5).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["line 04"]).await;
+    tokio::time::timeout(TIMEOUT, daemon.backend.subscribed())
+        .await
+        .expect("the app never subscribed to events");
+
+    press(&app, &mut terminal, KeyCode::Char('f')).await;
+    type_text(&app, &mut terminal, "fresh").await;
+    press(&app, &mut terminal, KeyCode::Enter).await;
+    press(&app, &mut terminal, KeyCode::Char('p')).await;
+    for line in ["stale 1", "fresh 1", "stale 2", "fresh 2"] {
+        daemon.backend.log("a1a1a1a1a1a1a1a1", Stream::StdOut, line);
+    }
+    // Lines arrive in order, so "stale 2" went through before "fresh 2" was counted
+    let screen = wait_for(&app, &mut terminal, &["paused, 2 new lines"]).await;
+    assert!(row_of(&screen, "fresh 1").is_none());
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('p')).await;
+    assert!(row_of(&screen, "fresh 2").is_some());
+    assert!(row_of(&screen, "stale").is_none());
+}
+
+async fn type_text(app: &App, terminal: &mut Terminal<TestBackend>, text: &str) -> Vec<String> {
+    let mut screen = Vec::new();
+    for c in text.chars() {
+        screen = press(app, terminal, KeyCode::Char(c)).await;
+    }
+    screen
+}
+
+/// The cell where `text` starts on screen.
+fn cell_of(terminal: &Terminal<TestBackend>, text: &str) -> Option<Cell> {
+    let buffer = terminal.backend().buffer();
+    let width = text.chars().count() as u16;
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width.saturating_sub(width) {
+            let found: String = (x..x + width).map(|x| buffer[(x, y)].symbol()).collect();
+            if found == text {
+                return Some(buffer[(x, y)].clone());
+            }
+        }
+    }
+    None
+}
+
+fn background_of(terminal: &Terminal<TestBackend>, text: &str) -> Option<Color> {
+    cell_of(terminal, text).map(|cell| cell.bg)
+}
+
+fn foreground_of(terminal: &Terminal<TestBackend>, text: &str) -> Option<Color> {
+    cell_of(terminal, text).map(|cell| cell.fg)
+}
+
+#[tokio::test]
+async fn searches_and_highlights_matches() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(
+        &app,
+        &mut terminal,
+        &["GET /health 200", "ERROR deadlock detected"],
+    )
+    .await;
+
+    press(&app, &mut terminal, KeyCode::Char('/')).await;
+    let screen = type_text(&app, &mut terminal, "error").await;
+    assert!(row_of(&screen, "/error█  match 2/2").is_some());
+    // The latest match is the current one
+    assert_eq!(
+        background_of(&terminal, "ERROR deadlock"),
+        Some(Color::Magenta)
+    );
+    assert_eq!(
+        background_of(&terminal, "ERROR payment"),
+        Some(Color::Yellow)
+    );
+    assert_eq!(background_of(&terminal, "GET /health"), Some(Color::Reset));
+
+    let screen = press(&app, &mut terminal, KeyCode::Enter).await;
+    assert!(row_of(&screen, "/error match 2/2").is_some());
+    let screen = press(&app, &mut terminal, KeyCode::Char('n')).await;
+    assert!(row_of(&screen, "/error match 1/2").is_some());
+    assert_eq!(
+        background_of(&terminal, "ERROR payment"),
+        Some(Color::Magenta)
+    );
+    let screen = press(&app, &mut terminal, KeyCode::Char('N')).await;
+    assert!(row_of(&screen, "/error match 2/2").is_some());
+
+    let screen = press(&app, &mut terminal, KeyCode::Esc).await;
+    assert!(row_of(&screen, "/error").is_none());
+    assert_eq!(
+        background_of(&terminal, "ERROR deadlock"),
+        Some(Color::Reset)
+    );
+    // Without a search n selects no container again
+    let screen = press(&app, &mut terminal, KeyCode::Char('n')).await;
+    assert!(row_of(&screen, "ERROR deadlock detected").is_none());
+}
+
+#[tokio::test]
+async fn search_is_smart_case_and_falls_back_to_plain_text() {
+    let backend = FakeBackend::new();
+    backend.add(
+        FakeContainer::new("a1a1a1a1a1a1a1a1", "api")
+            .line(Stream::StdOut, "retry (attempt 2)")
+            .line(Stream::StdOut, "Retry later"),
+    );
+    let daemon = FakeDaemon::start(backend).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["Retry later"]).await;
+
+    press(&app, &mut terminal, KeyCode::Char('/')).await;
+    let screen = type_text(&app, &mut terminal, "retry").await;
+    assert!(row_of(&screen, "match 2/2").is_some());
+    let screen = type_text(&app, &mut terminal, " (").await;
+    assert!(row_of(&screen, "match 1/1 (plain text)").is_some());
+
+    for _ in 0.." (".len() {
+        press(&app, &mut terminal, KeyCode::Backspace).await;
+    }
+    press(&app, &mut terminal, KeyCode::Esc).await;
+    press(&app, &mut terminal, KeyCode::Char('/')).await;
+    let screen = type_text(&app, &mut terminal, "Retry").await;
+    assert!(row_of(&screen, "match 1/1").is_some());
+    let screen = type_text(&app, &mut terminal, "x").await;
+    assert!(row_of(&screen, "no matches").is_some());
+}
+
+#[tokio::test]
+async fn search_scrolls_to_the_match() {
+    let daemon = numbered_lines(60).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    let screen = wait_for(&app, &mut terminal, &["line 59"]).await;
+    assert!(row_of(&screen, "line 05").is_none());
+
+    press(&app, &mut terminal, KeyCode::Char('/')).await;
+    type_text(&app, &mut terminal, "line 05").await;
+    let screen = press(&app, &mut terminal, KeyCode::Enter).await;
+    assert!(row_of(&screen, "line 05").is_some());
+    assert!(row_of(&screen, "line 59").is_none());
+    assert!(row_of(&screen, "paused").is_some());
+}
+
+#[tokio::test]
+async fn filters_lines_live() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(
+        &app,
+        &mut terminal,
+        &["GET /health 200", "ERROR deadlock detected"],
+    )
+    .await;
+    tokio::time::timeout(TIMEOUT, daemon.backend.subscribed())
+        .await
+        .expect("the app never subscribed to events");
+
+    press(&app, &mut terminal, KeyCode::Char('f')).await;
+    let screen = type_text(&app, &mut terminal, "error").await;
+    assert!(row_of(&screen, "filter: error█").is_some());
+    assert!(row_of(&screen, "ERROR payment gateway timeout").is_some());
+    assert!(row_of(&screen, "ERROR deadlock detected").is_some());
+    assert!(row_of(&screen, "GET /health 200").is_none());
+
+    let screen = press(&app, &mut terminal, KeyCode::Enter).await;
+    assert!(row_of(&screen, "LOGS filter: error").is_some());
+    // Arriving lines go through the filter too
+    daemon
+        .backend
+        .log("a1a1a1a1a1a1a1a1", Stream::StdOut, "GET /cart 200");
+    daemon
+        .backend
+        .log("a1a1a1a1a1a1a1a1", Stream::StdErr, "ERROR cart is gone");
+    let screen = wait_for(&app, &mut terminal, &["ERROR cart is gone"]).await;
+    assert!(row_of(&screen, "GET /cart 200").is_none());
+
+    // Selecting containers keeps the filter
+    press(&app, &mut terminal, KeyCode::Down).await;
+    let screen = press(&app, &mut terminal, KeyCode::Char(' ')).await;
+    assert!(row_of(&screen, "ERROR cart is gone").is_none());
+    assert!(row_of(&screen, "ERROR deadlock detected").is_some());
+    press(&app, &mut terminal, KeyCode::Char('a')).await;
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('F')).await;
+    assert!(row_of(&screen, "filter:").is_none());
+    assert!(row_of(&screen, "GET /cart 200").is_some());
+}
+
+#[tokio::test]
+async fn filters_out_matching_lines_and_cancels() {
+    let daemon = FakeDaemon::start(load_fixture("shop")).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["GET /health 200"]).await;
+
+    press(&app, &mut terminal, KeyCode::Char('f')).await;
+    let screen = type_text(&app, &mut terminal, "!get").await;
+    assert!(row_of(&screen, "GET /").is_none());
+    assert!(row_of(&screen, "listening on :8080").is_some());
+    press(&app, &mut terminal, KeyCode::Enter).await;
+
+    // Editing starts from the current filter and Esc restores it
+    let screen = press(&app, &mut terminal, KeyCode::Char('f')).await;
+    assert!(row_of(&screen, "filter: !get█").is_some());
+    let screen = type_text(&app, &mut terminal, "|ready").await;
+    assert!(row_of(&screen, "database system is ready").is_none());
+    let screen = press(&app, &mut terminal, KeyCode::Esc).await;
+    assert!(row_of(&screen, "LOGS filter: !get").is_some());
+    assert!(row_of(&screen, "database system is ready").is_some());
+    assert!(row_of(&screen, "GET /").is_none());
+}
+
+#[tokio::test]
+async fn renders_output_colours_and_strips_them_on_demand() {
+    let backend = FakeBackend::new();
+    backend.add(
+        FakeContainer::new("a1a1a1a1a1a1a1a1", "api")
+            .line(Stream::StdOut, "\x1b[2K\x1b[32mINFO\x1b[0m ready")
+            .line(
+                Stream::StdOut,
+                "\x1b]0;title\x07\x1b[1;31mERROR\x1b[0m down",
+            ),
+    );
+    let daemon = FakeDaemon::start(backend).await;
+    let app = start(&daemon).await;
+    let mut terminal = terminal();
+    wait_for(&app, &mut terminal, &["o: INFO ready", "o: ERROR down"]).await;
+    assert_eq!(foreground_of(&terminal, "INFO"), Some(Color::Green));
+    assert_eq!(foreground_of(&terminal, "ERROR"), Some(Color::Red));
+    assert_eq!(foreground_of(&terminal, " ready"), Some(Color::Reset));
+
+    // Search highlighting goes on top of the colours
+    press(&app, &mut terminal, KeyCode::Char('/')).await;
+    type_text(&app, &mut terminal, "ror d").await;
+    assert_eq!(foreground_of(&terminal, "ERROR"), Some(Color::Red));
+    assert_eq!(background_of(&terminal, "ROR d"), Some(Color::Magenta));
+    press(&app, &mut terminal, KeyCode::Esc).await;
+
+    let screen = press(&app, &mut terminal, KeyCode::Char('c')).await;
+    assert!(row_of(&screen, "o: ERROR down").is_some());
+    assert_eq!(foreground_of(&terminal, "INFO"), Some(Color::Reset));
+    assert_eq!(foreground_of(&terminal, "ERROR"), Some(Color::Reset));
+}